@@ -0,0 +1,81 @@
+#ifndef FI_ETHR_RESOLVER_H
+#define FI_ETHR_RESOLVER_H
+
+#include <stdint.h>
+
+#define ETHR_OK 0
+
+#define ETHR_ERROR 1
+
+#define ETHR_INVALID_ARGUMENT 2
+
+#define ETHR_PANIC 3
+
+typedef struct EthrResolverHandle EthrResolverHandle;
+
+struct EthrResolverHandle *ethr_resolver_new(void);
+
+/**
+ * # Safety
+ *
+ * `handle` must be null or a pointer returned by `ethr_resolver_new` that has not been freed.
+ */
+void ethr_resolver_free(struct EthrResolverHandle *handle);
+
+/**
+ * # Safety
+ *
+ * `handle` must be a live pointer returned by `ethr_resolver_new`. `did`, `rpc_url` and
+ * `accept` must be null-terminated strings. `out_json` and `out_err` must be writable; any
+ * string written to them must be released with `ethr_string_free`.
+ */
+int ethr_resolver_resolve(const struct EthrResolverHandle *handle,
+                          const char *did,
+                          const char *rpc_url,
+                          const char *accept,
+                          char **out_json,
+                          char **out_err);
+
+/**
+ * # Safety
+ *
+ * Same requirements as `ethr_resolver_resolve`. The representation for `accept`, including the
+ * binary `application/did+cbor`, is written to `out_bytes` and `out_len` and must be released
+ * with `ethr_bytes_free`.
+ */
+int ethr_resolver_resolve_representation(const struct EthrResolverHandle *handle,
+                                         const char *did,
+                                         const char *rpc_url,
+                                         const char *accept,
+                                         uint8_t **out_bytes,
+                                         uintptr_t *out_len,
+                                         char **out_err);
+
+/**
+ * # Safety
+ *
+ * Same requirements as `ethr_resolver_resolve`, without the handle. A runtime is created for
+ * the duration of the call.
+ */
+int ethr_resolve(const char *did,
+                 const char *rpc_url,
+                 const char *accept,
+                 char **out_json,
+                 char **out_err);
+
+/**
+ * # Safety
+ *
+ * `value` must be null or a string returned through an `out_json` or `out_err` parameter.
+ */
+void ethr_string_free(char *value);
+
+/**
+ * # Safety
+ *
+ * `value` must be null or a buffer returned through `out_bytes`, with `len` set to the length
+ * written to `out_len` alongside it.
+ */
+void ethr_bytes_free(uint8_t *value, uintptr_t len);
+
+#endif /* FI_ETHR_RESOLVER_H */
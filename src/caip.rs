@@ -0,0 +1,211 @@
+use fi_common::{did::DidDocument, error::Error, keys::KeyPair};
+use regex::Regex;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+use crate::representation::document_context;
+use crate::verification::ECDSA_SECP256K1_RECOVERY_METHOD2020;
+use crate::{get_identity_address, get_network};
+
+pub const EIP155: &str = "eip155";
+
+static CHAIN_ID: OnceLock<Regex> = OnceLock::new();
+static ACCOUNT_ADDRESS: OnceLock<Regex> = OnceLock::new();
+static ETHEREUM_ADDRESS: OnceLock<Regex> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChainId {
+    pub namespace: String,
+    pub reference: String,
+}
+
+impl ChainId {
+    pub fn eip155(chain_id: u64) -> ChainId {
+        ChainId {
+            namespace: String::from(EIP155),
+            reference: chain_id.to_string(),
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<ChainId, Error> {
+        let regex = CHAIN_ID
+            .get_or_init(|| Regex::new("^([-a-z0-9]{3,8}):([-_a-zA-Z0-9]{1,32})$").unwrap());
+
+        match regex.captures(value) {
+            Some(captures) => Ok(ChainId {
+                namespace: String::from(&captures[1]),
+                reference: String::from(&captures[2]),
+            }),
+            None => Err(Error::new(
+                format!("Not a valid CAIP-2 chain id: {}", value).as_str(),
+            )),
+        }
+    }
+
+    pub fn eip155_chain_id(&self) -> Option<u64> {
+        match self.namespace == EIP155 {
+            true => self.reference.parse::<u64>().ok(),
+            false => None,
+        }
+    }
+}
+
+impl Display for ChainId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.reference)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountId {
+    pub chain_id: ChainId,
+    pub address: String,
+}
+
+impl AccountId {
+    pub fn eip155(chain_id: u64, address: &str) -> AccountId {
+        AccountId {
+            chain_id: ChainId::eip155(chain_id),
+            address: String::from(address),
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<AccountId, Error> {
+        let (chain_id, address) = match value.rsplit_once(':') {
+            Some(val) => val,
+            None => {
+                return Err(Error::new(
+                    format!("Not a valid CAIP-10 account id: {}", value).as_str(),
+                ))
+            }
+        };
+
+        let chain_id = ChainId::parse(chain_id)?;
+
+        let regex = ACCOUNT_ADDRESS.get_or_init(|| Regex::new("^[-.%a-zA-Z0-9]{1,128}$").unwrap());
+
+        if !regex.is_match(address) {
+            return Err(Error::new(
+                format!("Not a valid CAIP-10 account id: {}", value).as_str(),
+            ));
+        }
+
+        if chain_id.namespace == EIP155 && !is_ethereum_address(address) {
+            return Err(Error::new(
+                format!("Not a valid eip155 account address: {}", address).as_str(),
+            ));
+        }
+
+        Ok(AccountId {
+            chain_id,
+            address: String::from(address),
+        })
+    }
+}
+
+impl Display for AccountId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.chain_id, self.address)
+    }
+}
+
+pub fn ethr_to_pkh(did: &str, chain_id: Option<u64>) -> Result<String, Error> {
+    let address = get_identity_address(did)?;
+
+    let chain_id = match chain_id {
+        Some(val) => val,
+        None => {
+            let network = get_network(did);
+            match known_chain_id(network.as_str()).or(network
+                .strip_prefix("0x")
+                .and_then(|val| u64::from_str_radix(val, 16).ok()))
+            {
+                Some(val) => val,
+                None => {
+                    return Err(Error::new(
+                        format!("The chain id of network {} is unknown", network).as_str(),
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok(format!(
+        "did:pkh:{}",
+        AccountId::eip155(chain_id, format!("0x{}", address).as_str())
+    ))
+}
+
+pub fn known_chain_id(network: &str) -> Option<u64> {
+    match network {
+        "mainnet" => Some(1),
+        "goerli" => Some(5),
+        "sepolia" => Some(11155111),
+        "polygon" => Some(137),
+        "linea" => Some(59144),
+        _ => None,
+    }
+}
+
+pub fn pkh_to_ethr(did: &str) -> Result<String, Error> {
+    let account_id = pkh_account_id(did)?;
+
+    match account_id.chain_id.eip155_chain_id() {
+        Some(1) => Ok(format!("did:ethr:{}", account_id.address)),
+        Some(val) => Ok(format!("did:ethr:{:#x}:{}", val, account_id.address)),
+        None => Err(Error::new(
+            format!("Not an eip155 did:pkh: {}", did).as_str(),
+        )),
+    }
+}
+
+pub fn pkh_document(did: &str) -> Result<DidDocument, Error> {
+    let account_id = pkh_account_id(did)?;
+    let method_id = format!("{}#blockchainAccountId", did);
+
+    let mut document = DidDocument {
+        context: Vec::new(),
+        id: String::from(did),
+        verification_method: Some(vec![KeyPair {
+            _type: String::from(ECDSA_SECP256K1_RECOVERY_METHOD2020),
+            blockchain_account_id: Some(account_id.to_string()),
+            id: Some(method_id.clone()),
+            context: None,
+            public_key_base58: None,
+            private_key_base58: None,
+            public_key_multibase: None,
+            private_key_multibase: None,
+            revoked: None,
+            controller: Some(String::from(did)),
+            public_key_hex: None,
+            public_key_base64: None,
+            public_key_pem: None,
+            private_key_hex: None,
+            private_key_base64: None,
+            private_key_pem: None,
+            value: None,
+        }]),
+        authentication: Some(vec![method_id.clone()]),
+        assertion_method: Some(vec![method_id]),
+        capability_delegation: None,
+        capability_invocation: None,
+        key_agreement: None,
+        services: None,
+    };
+
+    document.context = document_context(&document);
+    Ok(document)
+}
+
+fn pkh_account_id(did: &str) -> Result<AccountId, Error> {
+    match did.strip_prefix("did:pkh:") {
+        Some(val) => AccountId::parse(val),
+        None => Err(Error::new(format!("Not a valid did:pkh: {}", did).as_str())),
+    }
+}
+
+fn is_ethereum_address(address: &str) -> bool {
+    ETHEREUM_ADDRESS
+        .get_or_init(|| Regex::new("^0x[0-9a-fA-F]{40}$").unwrap())
+        .is_match(address)
+}
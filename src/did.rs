@@ -1,4 +1,4 @@
-use ethers::providers::{Http, Middleware, Provider};
+use ethers::providers::{JsonRpcClient, Middleware, Provider};
 use ethers::types::U256;
 use fi_common::did::Service;
 use fi_common::error::Error;
@@ -6,11 +6,19 @@ use fi_common::{did::DidDocument, keys::KeyPair};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::util::{get_public_key, strip0x};
+use crate::events::attribute_handler::AttributeHandlers;
+use crate::profile::{InvalidKeyPolicy, InvalidServicePolicy, OutputProfile};
+use crate::transport::RpcTransport;
+use crate::util::{get_public_key, is_valid_public_key, public_key_hex_to_address, strip0x};
 use crate::verification::{
     ECDSA_SECP256K1_RECOVERY_METHOD2020, ECDSA_SECP256K1_VERIFICATION_KEY2019,
 };
 
+enum ControllerState {
+    OriginalKey(String),
+    Owner,
+}
+
 pub struct DidDoc {
     pub doc: DidDocument,
     pub deactivated: bool,
@@ -24,6 +32,11 @@ pub struct DidDoc {
     pub pks: HashMap<String, KeyPair>,
     pub services: HashMap<String, Service>,
     pub chain_id: Option<U256>,
+    pub now: Option<U256>,
+    pub invalid_key_policy: InvalidKeyPolicy,
+    pub invalid_keys: Vec<String>,
+    pub invalid_service_policy: InvalidServicePolicy,
+    pub attribute_handlers: AttributeHandlers,
 }
 
 impl DidDoc {
@@ -41,10 +54,52 @@ impl DidDoc {
             pks: HashMap::new(),
             services: HashMap::new(),
             chain_id: None,
+            now: None,
+            invalid_key_policy: InvalidKeyPolicy::default(),
+            invalid_keys: Vec::new(),
+            invalid_service_policy: InvalidServicePolicy::default(),
+            attribute_handlers: AttributeHandlers::default(),
         }
     }
 
-    pub fn finalize(&mut self) -> Result<(DidDocument, bool, Option<u64>), Error> {
+    pub fn apply_profile(&mut self, profile: &OutputProfile) {
+        self.invalid_key_policy = profile.invalid_keys;
+        self.invalid_service_policy = profile.invalid_services;
+        self.attribute_handlers = profile.attribute_handlers.clone();
+    }
+
+    pub fn check_public_key(&mut self, id: &str, value: &[u8]) -> bool {
+        if is_valid_public_key(value) {
+            return true;
+        }
+
+        match self.invalid_key_policy {
+            InvalidKeyPolicy::Flag => {
+                self.invalid_keys.push(String::from(id));
+                true
+            }
+            InvalidKeyPolicy::Drop => false,
+        }
+    }
+
+    /// Whether `id` is already used by the document, ignoring the entry the
+    /// event at `event_index` is about to replace.
+    pub fn has_id(&self, id: &str, event_index: &str) -> bool {
+        let did = self.doc.id.as_str();
+
+        id == format!("{}#controller", did)
+            || id == format!("{}#controllerKey", did)
+            || self
+                .pks
+                .iter()
+                .any(|(key, pk)| key != event_index && pk.id.as_deref() == Some(id))
+            || self
+                .services
+                .iter()
+                .any(|(key, service)| key != event_index && service.id == id)
+    }
+
+    pub fn finalize(&mut self) -> Result<(DidDocument, bool, Option<u64>, Vec<String>), Error> {
         let mut public_keys = vec![KeyPair {
             _type: String::from(ECDSA_SECP256K1_RECOVERY_METHOD2020),
             blockchain_account_id: Some(format!(
@@ -100,44 +155,31 @@ impl DidDoc {
         authentication_vec.push(format!("{}#controller", self.doc.id.clone()));
         assertion_method_vec.push(format!("{}#controller", self.doc.id.clone()));
 
-        match get_public_key(self.doc.id.clone()) {
-            Some(controller_key_val) => {
-                let did = String::from(self.doc.id.clone().split("?").collect::<Vec<&str>>()[0]);
-
-                let components = did.split(":").collect::<Vec<&str>>();
-                let address: String = String::from(components[components.len() - 1]);
-                if self
-                    .controller
-                    .clone()
-                    .is_some_and(|controller| controller.eq(&address))
-                {
-                    let controller_key = KeyPair {
-                        _type: String::from(ECDSA_SECP256K1_VERIFICATION_KEY2019),
-                        blockchain_account_id: None,
-                        id: Some(format!("{}#controllerKey", self.doc.id.clone())),
-                        context: None,
-                        public_key_base58: None,
-                        private_key_base58: None,
-                        public_key_multibase: None,
-                        private_key_multibase: None,
-                        revoked: None,
-                        controller: Some(self.doc.id.clone()),
-                        public_key_hex: None,
-                        public_key_base64: None,
-                        public_key_pem: None,
-                        private_key_hex: Some(strip0x(controller_key_val)),
-                        private_key_base64: None,
-                        private_key_pem: None,
-                        value: None,
-                    };
-
-                    public_keys.push(controller_key);
-                    authentication_vec.push(format!("{}#controllerKey", self.doc.id.clone()));
-                    assertion_method_vec.push(format!("{}#controllerKey", self.doc.id.clone()));
-                }
-            }
-            None => {}
-        };
+        if let ControllerState::OriginalKey(public_key) = self.controller_state() {
+            let controller_key = KeyPair {
+                _type: String::from(ECDSA_SECP256K1_VERIFICATION_KEY2019),
+                blockchain_account_id: None,
+                id: Some(format!("{}#controllerKey", self.doc.id.clone())),
+                context: None,
+                public_key_base58: None,
+                private_key_base58: None,
+                public_key_multibase: None,
+                private_key_multibase: None,
+                revoked: None,
+                controller: Some(self.doc.id.clone()),
+                public_key_hex: Some(public_key),
+                public_key_base64: None,
+                public_key_pem: None,
+                private_key_hex: None,
+                private_key_base64: None,
+                private_key_pem: None,
+                value: None,
+            };
+
+            public_keys.push(controller_key);
+            authentication_vec.push(format!("{}#controllerKey", self.doc.id.clone()));
+            assertion_method_vec.push(format!("{}#controllerKey", self.doc.id.clone()));
+        }
 
         let mut signing_refs = self
             .signing_refs
@@ -171,8 +213,8 @@ impl DidDoc {
             key_agreement_vec.append(&mut key_agreement_refs);
         }
 
-        self.doc.authentication = Some(authentication_vec);
-        self.doc.assertion_method = Some(assertion_method_vec);
+        self.doc.authentication = Some(dedupe(authentication_vec));
+        self.doc.assertion_method = Some(dedupe(assertion_method_vec));
         self.doc.verification_method = Some(verification_method_vec);
         self.doc.key_agreement = Some(key_agreement_vec);
         self.doc.services = Some(services_vec);
@@ -180,11 +222,11 @@ impl DidDoc {
         Ok((
             match self.deactivated {
                 true => DidDocument {
-                    context: Vec::new(),
+                    context: self.doc.context.clone(),
                     id: self.doc.id.clone(),
-                    verification_method: self.doc.verification_method.take(),
-                    authentication: self.doc.authentication.take(),
-                    assertion_method: None,
+                    verification_method: Some(Vec::new()),
+                    authentication: Some(Vec::new()),
+                    assertion_method: Some(Vec::new()),
                     capability_delegation: None,
                     capability_invocation: None,
                     key_agreement: None,
@@ -194,10 +236,31 @@ impl DidDoc {
             },
             self.deactivated,
             self.version_id,
+            self.invalid_keys.clone(),
         ))
     }
 
-    pub async fn chain_id_add(&mut self, client: &Arc<Provider<Http>>) -> Result<(), Error> {
+    fn controller_state(&self) -> ControllerState {
+        let public_key = match get_public_key(self.doc.id.clone()) {
+            Some(val) => val,
+            None => return ControllerState::Owner,
+        };
+
+        match (
+            public_key_hex_to_address(public_key.as_str()),
+            self.controller.clone(),
+        ) {
+            (Some(address), Some(controller)) if address.eq_ignore_ascii_case(&controller) => {
+                ControllerState::OriginalKey(strip0x(public_key))
+            }
+            _ => ControllerState::Owner,
+        }
+    }
+
+    pub async fn chain_id_add<C: JsonRpcClient + 'static>(
+        &mut self,
+        client: &Arc<Provider<RpcTransport<C>>>,
+    ) -> Result<(), Error> {
         if self.chain_id.is_none() {
             let chain_id_result = client.get_chainid().await;
 
@@ -211,3 +274,13 @@ impl DidDoc {
         Ok(())
     }
 }
+
+fn dedupe(values: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for value in values {
+        if !result.contains(&value) {
+            result.push(value);
+        }
+    }
+    result
+}
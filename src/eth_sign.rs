@@ -0,0 +1,64 @@
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::utils::hash_message;
+use fi_common::{did::DidDocument, error::Error};
+
+use crate::representation::document_value;
+use crate::signature::{normalize_recovery_id, recovery_methods, verify_with_method};
+use crate::util::strip0x;
+
+pub fn verify_personal_message(
+    did_doc: &DidDocument,
+    message: &[u8],
+    signature: &str,
+) -> Result<String, Error> {
+    verify_digest(did_doc, hash_message(message).0, signature)
+}
+
+pub fn verify_typed_data(
+    did_doc: &DidDocument,
+    typed_data: &TypedData,
+    signature: &str,
+) -> Result<String, Error> {
+    let digest = match typed_data.encode_eip712() {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    verify_digest(did_doc, digest, signature)
+}
+
+fn verify_digest(
+    did_doc: &DidDocument,
+    digest: [u8; 32],
+    signature: &str,
+) -> Result<String, Error> {
+    let signature = match hex::decode(strip0x(String::from(signature))) {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    if signature.len() != 65 {
+        return Err(Error::new("Signature must be 65 bytes"));
+    }
+
+    let recovery_id = match normalize_recovery_id(signature[64]) {
+        Some(val) => val,
+        None => return Err(Error::new("Invalid recovery id in signature")),
+    };
+
+    let did_doc = document_value(did_doc)?;
+
+    for method in recovery_methods(&did_doc) {
+        if verify_with_method(&method, digest, &signature[..64], Some(recovery_id)) {
+            return Ok(String::from(method["id"].as_str().unwrap_or("")));
+        }
+    }
+
+    Err(Error::new(
+        format!(
+            "No recovery method of {} matches the signature",
+            did_doc["id"].as_str().unwrap_or("")
+        )
+        .as_str(),
+    ))
+}
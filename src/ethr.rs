@@ -1,101 +1,395 @@
-use ethers::abi::Abi;
-use ethers::contract::Contract;
-use ethers::providers::{Http, Middleware, Provider};
+use ethers::abi::{Abi, RawLog};
+use ethers::contract::{Contract, EthEvent};
+use ethers::providers::{Http, JsonRpcClient, Middleware, Provider};
 use ethers::types::{Address, BlockNumber, Filter, Log, H160, U256, U64};
 use fi_common::did::DidDocument;
 use fi_common::error::Error;
-use std::{sync::Arc, time::Duration};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 use crate::did::DidDoc;
 use crate::events::attribute_changed::{DIDAttributeChanged, DID_ATTRIBUTE_CHANGED_TOPIC};
 use crate::events::delegate_changed::{DIDDelegateChanged, DID_DELEGATE_CHANGED_TOPIC};
 use crate::events::owner_changed::{DIDOwnerChanged, DID_OWNER_CHANGED_TOPIC};
 use crate::events::DiDEthrChangeEvent;
+use crate::history::RegistryEvent;
+use crate::profile::OutputProfile;
+use crate::transport::{RateLimiter, RpcPolicy, RpcTransport};
+
+pub const DEFAULT_REGISTRY: &str = "0xdca7ef03e98e0dc2b855be647c39abe984fcf21b";
+
+const LOG_BLOCK_RANGE: u64 = 5_000;
+
+static REGISTRY_ABI: OnceLock<Abi> = OnceLock::new();
 
 pub async fn build_did_doc_from_logs(
     provider_url: &str,
     address: &str,
     did_doc: &mut DidDocument,
-) -> Result<(DidDocument, bool, Option<u64>), Error> {
-    let provider_result = Provider::<Http>::try_from(provider_url);
-    let provider = match provider_result {
-        Ok(val) => val.interval(Duration::from_secs(2)),
-        Err(error) => {
-            return Err(Error::new(error.to_string().as_str()));
-        }
+    profile: &OutputProfile,
+) -> Result<(DidDocument, bool, Option<u64>, Vec<String>), Error> {
+    let client = match get_client(provider_url, RpcPolicy::default(), None) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
     };
 
-    let client: Arc<Provider<Http>> = Arc::new(provider);
+    let registry = match parse_address(DEFAULT_REGISTRY) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    build_did_doc_from_registry(&client, registry, address, did_doc, None, profile).await
+}
 
-    let contract_address = address.parse::<Address>().unwrap();
+pub async fn build_did_doc_from_registry<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    address: &str,
+    did_doc: &mut DidDocument,
+    chain_id: Option<u64>,
+    profile: &OutputProfile,
+) -> Result<(DidDocument, bool, Option<u64>, Vec<String>), Error> {
+    let identity = match parse_address(address) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
 
     let mut did = DidDoc::new(did_doc, false, Some(format!("0x{}", address)));
+    did.apply_profile(profile);
+    did.chain_id = chain_id.map(U256::from);
 
-    match did.chain_id_add(&client).await {
+    match did.chain_id_add(client).await {
         Ok(_val) => {}
         Err(error) => return Err(error),
     }
 
-    let contract_abi = include_bytes!("contract-abi.json");
-    let contract: ethers::contract::ContractInstance<Arc<Provider<Http>>, Provider<Http>> =
-        Contract::new(
-            contract_address,
-            Abi::load(&contract_abi[..]).unwrap(),
-            client.clone(),
-        );
+    did.now = match get_latest_timestamp(client).await {
+        Ok(val) => Some(U256::from(val)),
+        Err(error) => return Err(error),
+    };
 
-    let logs = match get_logs(contract, contract_address, client).await {
+    let logs = match get_identity_logs(client, registry, identity).await {
         Ok(val) => val,
         Err(error) => return Err(error),
     };
 
+    match apply_logs(&mut did, logs, Some(registry)) {
+        Ok(_val) => {}
+        Err(error) => return Err(error),
+    };
+
+    did.finalize()
+}
+
+pub async fn get_identity_logs<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    identity: H160,
+) -> Result<Vec<Log>, Error> {
+    let contract = get_contract(registry, client.clone());
+
+    get_logs(contract, registry, identity, client.clone()).await
+}
+
+pub fn build_did_doc_from_fetched_logs(
+    address: &str,
+    did_doc: &mut DidDocument,
+    chain_id: u64,
+    now: Option<u64>,
+    logs: Vec<Log>,
+    profile: &OutputProfile,
+) -> Result<(DidDocument, bool, Option<u64>, Vec<String>), Error> {
+    let mut did = DidDoc::new(did_doc, false, Some(format!("0x{}", address)));
+    did.apply_profile(profile);
+    did.chain_id = Some(U256::from(chain_id));
+    did.now = now.map(U256::from);
+
+    apply_logs(&mut did, logs, None)?;
+
+    did.finalize()
+}
+
+fn apply_logs(
+    did: &mut DidDoc,
+    logs: Vec<Log>,
+    contract_address: Option<H160>,
+) -> Result<(), Error> {
     for log in logs {
         did.version_id = match log.block_number {
             Some(val) => Some(val.0[0]),
             None => None,
         };
 
-        if !log.address.eq(&contract_address) {
+        if contract_address.is_some_and(|address| !log.address.eq(&address)) {
             continue;
         }
 
-        match apply_change_to_did(&mut did, log) {
-            Ok(_val) => {}
+        apply_change_to_did(did, log)?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_history(provider_url: &str, address: &str) -> Result<Vec<RegistryEvent>, Error> {
+    let client = match get_client(provider_url, RpcPolicy::default(), None) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let registry = match parse_address(DEFAULT_REGISTRY) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    get_registry_history(&client, registry, address).await
+}
+
+pub async fn get_registry_history<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    address: &str,
+) -> Result<Vec<RegistryEvent>, Error> {
+    let identity = match parse_address(address) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let contract = get_contract(registry, client.clone());
+
+    let logs = match get_logs(contract, registry, identity, client.clone()).await {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let mut timestamps: HashMap<u64, Option<u64>> = HashMap::new();
+    let mut events = Vec::<RegistryEvent>::new();
+
+    for log in logs {
+        if !log.address.eq(&registry) {
+            continue;
+        }
+
+        let timestamp = match log.block_number {
+            Some(block_number) => match timestamps.get(&block_number.as_u64()) {
+                Some(val) => *val,
+                None => {
+                    let val = match get_block_timestamp(client, block_number).await {
+                        Ok(val) => val,
+                        Err(error) => return Err(error),
+                    };
+                    timestamps.insert(block_number.as_u64(), val);
+                    val
+                }
+            },
+            None => None,
+        };
+
+        match RegistryEvent::from_log(log, timestamp) {
+            Ok(val) => events.push(val),
             Err(error) => return Err(error),
         };
     }
 
-    did.finalize()
+    events.sort_by_key(|event| {
+        let metadata = event.metadata();
+        (metadata.block_number, metadata.log_index)
+    });
+
+    Ok(events)
 }
 
-async fn get_logs(
-    contract: ethers::contract::ContractInstance<Arc<Provider<Http>>, Provider<Http>>,
+pub fn get_client(
+    provider_url: &str,
+    policy: RpcPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+) -> Result<Arc<Provider<RpcTransport>>, Error> {
+    match get_http(provider_url) {
+        Ok(val) => Ok(new_client(val, policy, rate_limiter)),
+        Err(error) => Err(error),
+    }
+}
+
+pub fn get_http(provider_url: &str) -> Result<Http, Error> {
+    match Http::from_str(provider_url) {
+        Ok(val) => Ok(val),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+pub fn new_client<C: JsonRpcClient>(
+    inner: C,
+    policy: RpcPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+) -> Arc<Provider<RpcTransport<C>>> {
+    Arc::new(
+        Provider::new(RpcTransport::new(inner, policy, rate_limiter))
+            .interval(Duration::from_secs(2)),
+    )
+}
+
+pub fn parse_address(address: &str) -> Result<H160, Error> {
+    match address.parse::<Address>() {
+        Ok(val) => Ok(val),
+        Err(error) => Err(Error::new(
+            format!("Not a valid address: {} ({})", address, error).as_str(),
+        )),
+    }
+}
+
+fn get_contract<C: JsonRpcClient + 'static>(
     contract_address: H160,
-    client: Arc<Provider<Http>>,
+    client: Arc<Provider<RpcTransport<C>>>,
+) -> ethers::contract::ContractInstance<Arc<Provider<RpcTransport<C>>>, Provider<RpcTransport<C>>> {
+    let contract_abi = REGISTRY_ABI.get_or_init(|| {
+        let contract_abi = include_bytes!("contract-abi.json");
+        Abi::load(&contract_abi[..]).unwrap()
+    });
+
+    Contract::new(contract_address, contract_abi.clone(), client)
+}
+
+pub async fn get_registry_logs<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    from_block: u64,
+    to_block: u64,
 ) -> Result<Vec<Log>, Error> {
-    let block_tag: Option<BlockNumber> = None;
+    let event_topics = [
+        DID_ATTRIBUTE_CHANGED_TOPIC,
+        DID_DELEGATE_CHANGED_TOPIC,
+        DID_OWNER_CHANGED_TOPIC,
+    ];
+
     let mut event_log = Vec::<Log>::new();
+    let mut start = from_block;
 
-    let mut previous_change_option =
-        match get_previous_change(contract, contract_address, block_tag).await {
-            Ok(val) => Some(val),
-            Err(error) => return Err(error),
+    while start <= to_block {
+        let end = to_block.min(start.saturating_add(LOG_BLOCK_RANGE - 1));
+
+        let filter = Filter::new()
+            .address(ethers::types::ValueOrArray::Value(registry))
+            .events(event_topics)
+            .from_block(BlockNumber::Number(start.into()))
+            .to_block(BlockNumber::Number(end.into()));
+
+        match client.get_logs(&filter).await {
+            Ok(val) => event_log.extend(val),
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        start = match end.checked_add(1) {
+            Some(val) => val,
+            None => break,
+        };
+    }
+
+    Ok(event_log)
+}
+
+pub async fn get_identity_owner<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    identity: H160,
+) -> Result<H160, Error> {
+    let contract = get_contract(registry, client.clone());
+
+    let call = match contract.method::<_, Address>("identityOwner", identity) {
+        Ok(val) => val.call().await,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    match call {
+        Ok(val) => Ok(val),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+pub async fn get_valid_delegate<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    identity: H160,
+    delegate_type: [u8; 32],
+    delegate: H160,
+) -> Result<bool, Error> {
+    let contract = get_contract(registry, client.clone());
+
+    let call =
+        match contract.method::<_, bool>("validDelegate", (identity, delegate_type, delegate)) {
+            Ok(val) => val.call().await,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
         };
 
+    match call {
+        Ok(val) => Ok(val),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+pub fn to_bytes32(value: &str) -> Result<[u8; 32], Error> {
+    let mut bytes = [0u8; 32];
+
+    if value.len() > bytes.len() {
+        return Err(Error::new(
+            format!("Value does not fit in bytes32: {}", value).as_str(),
+        ));
+    }
+
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+    Ok(bytes)
+}
+
+pub async fn get_latest_timestamp<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+) -> Result<u64, Error> {
+    match client.get_block(BlockNumber::Latest).await {
+        Ok(Some(block)) => Ok(block.timestamp.as_u64()),
+        Ok(None) => Err(Error::new("The latest block could not be fetched")),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+pub async fn get_block_timestamp<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    block_number: U64,
+) -> Result<Option<u64>, Error> {
+    match client.get_block(block_number).await {
+        Ok(val) => Ok(val.map(|block| block.timestamp.as_u64())),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+async fn get_logs<C: JsonRpcClient + 'static>(
+    contract: ethers::contract::ContractInstance<
+        Arc<Provider<RpcTransport<C>>>,
+        Provider<RpcTransport<C>>,
+    >,
+    contract_address: H160,
+    identity: H160,
+    client: Arc<Provider<RpcTransport<C>>>,
+) -> Result<Vec<Log>, Error> {
+    let block_tag: Option<BlockNumber> = None;
+    let mut event_log = Vec::<Log>::new();
+
+    let mut previous_change = match get_previous_change(contract, identity, block_tag).await {
+        Ok(val) => val.as_u64(),
+        Err(error) => return Err(error),
+    };
+
     let event_topics = [
         DID_ATTRIBUTE_CHANGED_TOPIC,
         DID_DELEGATE_CHANGED_TOPIC,
         DID_OWNER_CHANGED_TOPIC,
     ];
 
-    while previous_change_option.is_some() {
-        let previous_change = previous_change_option.unwrap();
-
+    while previous_change != 0 {
         let filter = Filter::new()
             .address(ethers::types::ValueOrArray::Value(contract_address))
             .events(event_topics)
-            .from_block(BlockNumber::Number(previous_change.as_u64().into()))
-            .to_block(BlockNumber::Number(previous_change.as_u64().into()));
+            .topic1(identity)
+            .from_block(BlockNumber::Number(previous_change.into()))
+            .to_block(BlockNumber::Number(previous_change.into()));
 
         let mut logs = match client.get_logs(&filter).await {
             Ok(val) => val,
@@ -104,25 +398,108 @@ async fn get_logs(
             }
         };
 
-        logs.reverse();
-        previous_change_option = None;
+        previous_change = match verify_change_chain(identity, previous_change, &mut logs) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
 
-        logs.iter().for_each(|log| {
-            event_log.insert(0, log.clone());
-            if log
-                .block_number
-                .is_some_and(|block_number| block_number.as_u64() < previous_change.as_u64())
-            {
-                previous_change_option = Some(log.block_number.unwrap().into());
-            }
-        });
+        logs.append(&mut event_log);
+        event_log = logs;
     }
 
     Ok(event_log)
 }
 
-async fn get_previous_change(
-    contract: ethers::contract::ContractInstance<Arc<Provider<Http>>, Provider<Http>>,
+pub fn verify_change_chain(identity: H160, block: u64, logs: &mut [Log]) -> Result<u64, Error> {
+    let broken = |reason: String| {
+        Error::new(
+            format!(
+                "The registry event chain for {:#x} is broken: {}",
+                identity, reason
+            )
+            .as_str(),
+        )
+    };
+
+    if logs.is_empty() {
+        return Err(broken(format!(
+            "no events were returned for block {}",
+            block
+        )));
+    }
+
+    logs.sort_by_key(|log| log.log_index);
+
+    let mut earlier_change = 0;
+
+    for (position, log) in logs.iter().enumerate() {
+        if log.block_number != Some(block.into()) {
+            return Err(broken(format!(
+                "an event from another block was returned for block {}",
+                block
+            )));
+        }
+
+        let previous_change = match get_event_previous_change(log) {
+            Some(val) => val,
+            None => {
+                return Err(broken(format!(
+                    "an event in block {} could not be decoded",
+                    block
+                )))
+            }
+        };
+
+        match position {
+            0 if previous_change < U256::from(block) => earlier_change = previous_change.as_u64(),
+            0 => {
+                return Err(broken(format!(
+                    "the first event returned for block {} points to block {}",
+                    block, previous_change
+                )))
+            }
+            _ if previous_change != U256::from(block) => {
+                return Err(broken(format!(
+                    "an event in block {} points to block {}",
+                    block, previous_change
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(earlier_change)
+}
+
+pub fn get_event_previous_change(log: &Log) -> Option<U256> {
+    let raw_log = RawLog::from(log.clone());
+
+    if DIDAttributeChanged::is_event_of(&log.topics) {
+        return <DIDAttributeChanged as EthEvent>::decode_log(&raw_log)
+            .ok()
+            .map(|event| event.previous_change);
+    }
+
+    if DIDDelegateChanged::is_event_of(&log.topics) {
+        return <DIDDelegateChanged as EthEvent>::decode_log(&raw_log)
+            .ok()
+            .map(|event| event.previous_change);
+    }
+
+    if DIDOwnerChanged::is_event_of(&log.topics) {
+        return <DIDOwnerChanged as EthEvent>::decode_log(&raw_log)
+            .ok()
+            .map(|event| event.previous_change);
+    }
+
+    None
+}
+
+async fn get_previous_change<C: JsonRpcClient + 'static>(
+    contract: ethers::contract::ContractInstance<
+        Arc<Provider<RpcTransport<C>>>,
+        Provider<RpcTransport<C>>,
+    >,
     address: H160,
     block_tag: Option<BlockNumber>,
 ) -> Result<U64, Error> {
@@ -138,9 +515,20 @@ async fn get_previous_change(
         Err(error) => return Err(Error::new(error.to_string().as_str())),
     };
 
-    match call {
-        Ok(val) => Ok(val.as_u64().into()),
+    let changed = match call {
+        Ok(val) => val,
         Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    match u64::try_from(changed) {
+        Ok(val) => Ok(val.into()),
+        Err(_) => Err(Error::new(
+            format!(
+                "The registry reported an invalid change block for {:#x}: {}",
+                address, changed
+            )
+            .as_str(),
+        )),
     }
 }
 
@@ -150,13 +538,13 @@ fn apply_change_to_did(did_doc: &mut DidDoc, log: Log) -> Result<(), Error> {
     let event: Box<dyn DiDEthrChangeEvent>;
 
     if DIDAttributeChanged::is_event_of(&topics) {
-        let val = DIDAttributeChanged::from(log);
+        let val = DIDAttributeChanged::try_from(log)?;
         event = Box::new(val);
     } else if DIDDelegateChanged::is_event_of(&topics) {
-        let val = DIDDelegateChanged::from(log);
+        let val = DIDDelegateChanged::try_from(log)?;
         event = Box::new(val);
     } else if DIDOwnerChanged::is_event_of(&topics) {
-        let val = DIDOwnerChanged::from(log);
+        let val = DIDOwnerChanged::try_from(log)?;
         event = Box::new(val);
     } else {
         return Err(Error::new("Topic can't be identified"));
@@ -1,9 +1,14 @@
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-use super::DiDEthrChangeEvent;
+use super::{attribute_handler::AttributeChange, DiDEthrChangeEvent};
 use crate::{
     did::DidDoc,
-    util::{encode_base58, encode_base64, remove_zero_bytes, strip0x},
+    profile::InvalidServicePolicy,
+    util::{
+        encode_base58, encode_base64, is_uri_fragment, is_valid_service_endpoint,
+        remove_zero_bytes, strip0x,
+    },
     verification::LEGACY_ALGO_MAP,
 };
 use ethers::{
@@ -17,6 +22,8 @@ use serde_json::Value;
 
 const EVENT_NAME: &str = "DIDAttributeChanged";
 
+static ATTRIBUTE_NAME: OnceLock<Regex> = OnceLock::new();
+
 pub const DID_ATTRIBUTE_CHANGED_TOPIC: &str =
     "DIDAttributeChanged(address,bytes32,bytes,uint256,uint256)";
 
@@ -43,16 +50,20 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
 
         let event_index = format!("{}-{}-{}", EVENT_NAME, name, value);
 
-        let regex = match Regex::new("^did\\/(pub|svc)\\/(\\w+)(\\/(\\w+))?(\\/(\\w+))?$") {
-            Ok(val) => val,
-            Err(error) => {
-                return Err(Error::new(error.to_string().as_str()));
-            }
-        };
-
-        if regex.is_match(&name) {
-            let matched = name.split("/").collect::<Vec<&str>>();
+        if let Some(handler) = did_doc.attribute_handlers.find(&name) {
+            let change = AttributeChange {
+                identity: self.identity,
+                name: name.to_string(),
+                value: self.value.clone(),
+                valid_to: self.valid_to,
+                previous_change: self.previous_change,
+                event_index,
+            };
+
+            return handler.apply(&change, did_doc);
+        }
 
+        if let Some(matched) = split_name(&name) {
             let section = matched[1];
             let algorithm = matched[2];
 
@@ -60,7 +71,12 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
 
             match section {
                 "pub" => {
-                    let _type = match matched[3] {
+                    let purpose = match matched.get(3) {
+                        Some(val) => *val,
+                        None => return Ok(()),
+                    };
+
+                    let _type = match purpose {
                         "sigAuth" => "SignatureAuthentication2018",
                         "veriKey" => "VerificationKey2018",
                         "enc" => "KeyAgreementKey2019",
@@ -93,11 +109,11 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
                         false => String::from(algorithm),
                     };
 
-                    let encoding = matched[4];
+                    let encoding = matched.get(4).copied().unwrap_or_default();
                     match encoding {
-                        "hex" => pk.public_key_hex = Some(hex::encode(strip0x(value.to_string()))),
-                        "base64" => pk.public_key_base64 = Some(encode_base64(value.to_string())),
-                        "base58" => pk.public_key_base58 = Some(encode_base58(value.to_string())),
+                        "hex" => pk.public_key_hex = Some(hex::encode(&self.value)),
+                        "base64" => pk.public_key_base64 = Some(encode_base64(&self.value)),
+                        "base58" => pk.public_key_base58 = Some(encode_base58(&self.value)),
                         "pem" => {
                             pk.public_key_pem = Some(match remove_zero_bytes(value.to_string()) {
                                 Ok(val) => val,
@@ -107,9 +123,16 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
                         _ => pk.value = Some(strip0x(value.to_string())),
                     }
 
+                    if algorithm == "Secp256k1"
+                        && ["hex", "base64", "base58"].contains(&encoding)
+                        && !did_doc.check_public_key(pk.id.clone().unwrap().as_str(), &self.value)
+                    {
+                        return Ok(());
+                    }
+
                     did_doc.pks.insert(event_index.clone(), pk.clone());
 
-                    match matched[4] {
+                    match encoding {
                         "sigAuth" => {
                             did_doc
                                 .auth
@@ -134,15 +157,48 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
                         }
                     };
 
-                    let service_endpoint_result = Value::from_str(value.as_str());
+                    let mut id = format!("{}#service-{}", did, did_doc.service_count);
+
+                    let service_endpoint = match Value::from_str(value.as_str()) {
+                        Ok(Value::Object(mut val)) if val.contains_key("serviceEndpoint") => {
+                            if let Some(fragment) = val.get("id").and_then(|val| val.as_str()) {
+                                let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+                                if is_uri_fragment(fragment) {
+                                    id = format!("{}#{}", did, fragment);
+                                }
+                            }
+                            val.remove("serviceEndpoint").unwrap_or_default()
+                        }
+                        Ok(val) => val,
+                        Err(_error) => serde_json::Value::String(value),
+                    };
+
+                    if !is_valid_service_endpoint(&service_endpoint) {
+                        match did_doc.invalid_service_policy {
+                            InvalidServicePolicy::Drop => return Ok(()),
+                            InvalidServicePolicy::Reject => {
+                                return Err(Error::new(
+                                    format!("Invalid service endpoint for {}", id).as_str(),
+                                ))
+                            }
+                        }
+                    }
+
+                    if did_doc.has_id(&id, &event_index) {
+                        match did_doc.invalid_service_policy {
+                            InvalidServicePolicy::Drop => return Ok(()),
+                            InvalidServicePolicy::Reject => {
+                                return Err(Error::new(
+                                    format!("Duplicate service id {}", id).as_str(),
+                                ))
+                            }
+                        }
+                    }
 
                     let service = Service {
-                        id: format!("{}#service-{}", did, did_doc.service_count),
+                        id,
                         _type: String::from(algorithm),
-                        service_endpoint: match service_endpoint_result.is_ok() {
-                            true => service_endpoint_result.unwrap(),
-                            false => serde_json::Value::String(value),
-                        },
+                        service_endpoint,
                     };
 
                     did_doc.services.insert(event_index, service);
@@ -160,9 +216,36 @@ impl DiDEthrChangeEvent for DIDAttributeChanged {
     }
 }
 
-impl From<Log> for DIDAttributeChanged {
-    fn from(value: Log) -> Self {
-        let event = DIDAttributeChanged::decode_log(&value.into());
-        event.unwrap()
+impl DIDAttributeChanged {
+    /// Whether the name claims a public key without saying what it is for.
+    /// Such attributes are ignored when building a document.
+    pub fn is_malformed(&self) -> bool {
+        let name: Vec<u8> = self.name.into_iter().filter(|x| *x != 0).collect();
+
+        split_name(&String::from_utf8_lossy(&name))
+            .is_some_and(|matched| matched[1] == "pub" && matched.len() < 4)
+    }
+}
+
+fn split_name(name: &str) -> Option<Vec<&str>> {
+    let regex = ATTRIBUTE_NAME
+        .get_or_init(|| Regex::new("^did\\/(pub|svc)\\/(\\w+)(\\/(\\w+))?(\\/(\\w+))?$").unwrap());
+
+    match regex.is_match(name) {
+        true => Some(name.split("/").collect()),
+        false => None,
+    }
+}
+
+impl TryFrom<Log> for DIDAttributeChanged {
+    type Error = Error;
+
+    fn try_from(value: Log) -> Result<Self, Self::Error> {
+        match DIDAttributeChanged::decode_log(&value.into()) {
+            Ok(val) => Ok(val),
+            Err(error) => Err(Error::new(
+                format!("Could not decode {}: {}", EVENT_NAME, error).as_str(),
+            )),
+        }
     }
 }
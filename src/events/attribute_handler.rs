@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use ethers::types::{H160, U256};
+use fi_common::error::Error;
+use regex::Regex;
+
+use crate::did::DidDoc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeChange {
+    pub identity: H160,
+    pub name: String,
+    pub value: Vec<u8>,
+    pub valid_to: U256,
+    pub previous_change: U256,
+    pub event_index: String,
+}
+
+pub trait AttributeHandler: Send + Sync {
+    fn apply(&self, change: &AttributeChange, did_doc: &mut DidDoc) -> Result<(), Error>;
+}
+
+#[derive(Clone, Default)]
+pub struct AttributeHandlers {
+    handlers: Vec<(Regex, Arc<dyn AttributeHandler>)>,
+}
+
+impl AttributeHandlers {
+    pub fn new() -> AttributeHandlers {
+        AttributeHandlers::default()
+    }
+
+    pub fn register(
+        &mut self,
+        pattern: &str,
+        handler: Arc<dyn AttributeHandler>,
+    ) -> Result<(), Error> {
+        let regex = match Regex::new(pattern) {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        self.handlers.push((regex, handler));
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<Arc<dyn AttributeHandler>> {
+        self.handlers
+            .iter()
+            .find(|(regex, _handler)| regex.is_match(name))
+            .map(|(_regex, handler)| handler.clone())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl Debug for AttributeHandlers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.handlers.iter().map(|(regex, _handler)| regex.as_str()))
+            .finish()
+    }
+}
+
+impl PartialEq for AttributeHandlers {
+    fn eq(&self, other: &Self) -> bool {
+        self.handlers.len() == other.handlers.len()
+            && self
+                .handlers
+                .iter()
+                .zip(other.handlers.iter())
+                .all(|(a, b)| {
+                    a.0.as_str() == b.0.as_str()
+                        && Arc::as_ptr(&a.1) as *const () == Arc::as_ptr(&b.1) as *const ()
+                })
+    }
+}
+
+impl Eq for AttributeHandlers {}
@@ -10,17 +10,16 @@ use fi_common::{error::Error, keys::KeyPair};
 const EVENT_NAME: &str = "DIDDelegateChanged";
 
 pub const DID_DELEGATE_CHANGED_TOPIC: &str =
-    "DIDDelegateChanged(address,bytes32,bytes32,address,uint256,uint256)";
+    "DIDDelegateChanged(address,bytes32,address,uint256,uint256)";
 
 #[derive(Debug, Clone, EthEvent)]
 #[ethevent(
     name = "DIDDelegateChanged",
-    abi = "DIDDelegateChanged(address indexed identity, bytes32 delegateType, bytes32 name, address delegate, uint256 validTo, uint256 previousChange)"
+    abi = "DIDDelegateChanged(address indexed identity, bytes32 delegateType, address delegate, uint256 validTo, uint256 previousChange)"
 )]
 pub struct DIDDelegateChanged {
     pub identity: H160,
     pub delegate_type: [u8; 32],
-    pub name: [u8; 32],
     pub delegate: H160,
     pub valid_to: U256,
     pub previous_change: U256,
@@ -38,13 +37,7 @@ impl DiDEthrChangeEvent for DIDDelegateChanged {
                 }
             };
 
-        let delegate =
-            match String::from_utf8(self.delegate.0.into_iter().filter(|x| *x != 0).collect()) {
-                Ok(val) => val,
-                Err(error) => {
-                    return Err(Error::new(error.to_string().as_str()));
-                }
-            };
+        let delegate = format!("{:#x}", self.delegate);
 
         let event_index = format!("{}-{}-{}", EVENT_NAME, delegate_type, delegate);
 
@@ -52,6 +45,13 @@ impl DiDEthrChangeEvent for DIDDelegateChanged {
 
         let del_str = format!("{}#delegate-{}", did, did_doc.delegate_count);
 
+        if did_doc.now.is_some_and(|now| self.valid_to <= now) {
+            did_doc.auth.remove(&event_index);
+            did_doc.signing_refs.remove(&event_index);
+            did_doc.pks.remove(&event_index);
+            return Ok(());
+        }
+
         match delegate_type.as_str() {
             "sigAuth" => {
                 did_doc.auth.insert(event_index.clone(), del_str.clone());
@@ -98,9 +98,15 @@ impl DiDEthrChangeEvent for DIDDelegateChanged {
     }
 }
 
-impl From<Log> for DIDDelegateChanged {
-    fn from(value: Log) -> Self {
-        let event = DIDDelegateChanged::decode_log(&value.into());
-        event.unwrap()
+impl TryFrom<Log> for DIDDelegateChanged {
+    type Error = Error;
+
+    fn try_from(value: Log) -> Result<Self, Self::Error> {
+        match DIDDelegateChanged::decode_log(&value.into()) {
+            Ok(val) => Ok(val),
+            Err(error) => Err(Error::new(
+                format!("Could not decode {}: {}", EVENT_NAME, error).as_str(),
+            )),
+        }
     }
 }
@@ -3,6 +3,7 @@ use ethers::types::H256;
 use fi_common::error::Error;
 
 pub mod attribute_changed;
+pub mod attribute_handler;
 pub mod delegate_changed;
 pub mod owner_changed;
 
@@ -7,7 +7,6 @@ use ethers::{
 };
 use fi_common::error::Error;
 
-#[allow(dead_code)]
 const EVENT_NAME: &str = "DIDOwnerChanged";
 
 pub const DID_OWNER_CHANGED_TOPIC: &str = "DIDOwnerChanged(address,address,uint256)";
@@ -28,6 +27,7 @@ impl DiDEthrChangeEvent for DIDOwnerChanged {
         let controller = format!("0x{}", hex::encode(self.owner.0));
         did_doc.delegate_count = did_doc.delegate_count + 1;
         did_doc.controller = Some(controller);
+        did_doc.deactivated = self.owner.is_zero();
         Ok(())
     }
 
@@ -37,9 +37,15 @@ impl DiDEthrChangeEvent for DIDOwnerChanged {
     }
 }
 
-impl From<Log> for DIDOwnerChanged {
-    fn from(value: Log) -> Self {
-        let event = DIDOwnerChanged::decode_log(&value.into());
-        event.unwrap()
+impl TryFrom<Log> for DIDOwnerChanged {
+    type Error = Error;
+
+    fn try_from(value: Log) -> Result<Self, Self::Error> {
+        match DIDOwnerChanged::decode_log(&value.into()) {
+            Ok(val) => Ok(val),
+            Err(error) => Err(Error::new(
+                format!("Could not decode {}: {}", EVENT_NAME, error).as_str(),
+            )),
+        }
     }
 }
@@ -0,0 +1,259 @@
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{resolve_representation, DID_CBOR};
+
+pub const ETHR_OK: c_int = 0;
+pub const ETHR_ERROR: c_int = 1;
+pub const ETHR_INVALID_ARGUMENT: c_int = 2;
+pub const ETHR_PANIC: c_int = 3;
+
+pub struct EthrResolverHandle {
+    runtime: Runtime,
+}
+
+#[no_mangle]
+pub extern "C" fn ethr_resolver_new() -> *mut EthrResolverHandle {
+    let handle = catch_unwind(|| match Builder::new_multi_thread().enable_all().build() {
+        Ok(runtime) => Box::into_raw(Box::new(EthrResolverHandle { runtime })),
+        Err(_error) => ptr::null_mut(),
+    });
+
+    handle.unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+///
+/// `handle` must be null or a pointer returned by `ethr_resolver_new` that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_resolver_free(handle: *mut EthrResolverHandle) {
+    if !handle.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(handle))));
+    }
+}
+
+/// # Safety
+///
+/// `handle` must be a live pointer returned by `ethr_resolver_new`. `did`, `rpc_url` and
+/// `accept` must be null-terminated strings. `out_json` and `out_err` must be writable; any
+/// string written to them must be released with `ethr_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_resolver_resolve(
+    handle: *const EthrResolverHandle,
+    did: *const c_char,
+    rpc_url: *const c_char,
+    accept: *const c_char,
+    out_json: *mut *mut c_char,
+    out_err: *mut *mut c_char,
+) -> c_int {
+    let result = match handle.is_null() {
+        true => Err((
+            ETHR_INVALID_ARGUMENT,
+            String::from("Resolver handle is null"),
+        )),
+        false => guard(|| resolve_on(&(*handle).runtime, did, rpc_url, accept)),
+    };
+
+    finish_string(result, out_json, out_err)
+}
+
+/// # Safety
+///
+/// Same requirements as `ethr_resolver_resolve`. The representation for `accept`, including the
+/// binary `application/did+cbor`, is written to `out_bytes` and `out_len` and must be released
+/// with `ethr_bytes_free`.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_resolver_resolve_representation(
+    handle: *const EthrResolverHandle,
+    did: *const c_char,
+    rpc_url: *const c_char,
+    accept: *const c_char,
+    out_bytes: *mut *mut u8,
+    out_len: *mut usize,
+    out_err: *mut *mut c_char,
+) -> c_int {
+    let result = match handle.is_null() || out_bytes.is_null() || out_len.is_null() {
+        true => Err((
+            ETHR_INVALID_ARGUMENT,
+            String::from("The resolver handle, out_bytes and out_len must not be null"),
+        )),
+        false => guard(|| represent_on(&(*handle).runtime, did, rpc_url, accept)),
+    };
+
+    match result {
+        Ok(val) => {
+            write_bytes(out_bytes, out_len, Some(val));
+            write_null(out_err);
+            ETHR_OK
+        }
+        Err((status, message)) => {
+            write_bytes(out_bytes, out_len, None);
+            write_string(out_err, message.as_str());
+            status
+        }
+    }
+}
+
+/// # Safety
+///
+/// Same requirements as `ethr_resolver_resolve`, without the handle. A runtime is created for
+/// the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_resolve(
+    did: *const c_char,
+    rpc_url: *const c_char,
+    accept: *const c_char,
+    out_json: *mut *mut c_char,
+    out_err: *mut *mut c_char,
+) -> c_int {
+    let result = guard(|| {
+        let runtime = match Builder::new_current_thread().enable_all().build() {
+            Ok(val) => val,
+            Err(error) => return Err((ETHR_ERROR, error.to_string())),
+        };
+
+        resolve_on(&runtime, did, rpc_url, accept)
+    });
+
+    finish_string(result, out_json, out_err)
+}
+
+/// # Safety
+///
+/// `value` must be null or a string returned through an `out_json` or `out_err` parameter.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(CString::from_raw(value))));
+    }
+}
+
+/// # Safety
+///
+/// `value` must be null or a buffer returned through `out_bytes`, with `len` set to the length
+/// written to `out_len` alongside it.
+#[no_mangle]
+pub unsafe extern "C" fn ethr_bytes_free(value: *mut u8, len: usize) {
+    if !value.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(value, len)))
+        }));
+    }
+}
+
+/// A status code and the message to report through `out_err`.
+type Failure = (c_int, String);
+
+/// Runs `call`, turning a panic into `ETHR_PANIC`. Nothing is written to the out parameters
+/// until the result is known, so a panic can never leave a string behind.
+fn guard<T, F: FnOnce() -> Result<T, Failure>>(call: F) -> Result<T, Failure> {
+    match catch_unwind(AssertUnwindSafe(call)) {
+        Ok(val) => val,
+        Err(_panic) => Err((ETHR_PANIC, String::from("The resolver panicked"))),
+    }
+}
+
+unsafe fn finish_string(
+    result: Result<String, Failure>,
+    out_json: *mut *mut c_char,
+    out_err: *mut *mut c_char,
+) -> c_int {
+    match result {
+        Ok(val) => {
+            write_string(out_json, val.as_str());
+            write_null(out_err);
+            ETHR_OK
+        }
+        Err((status, message)) => {
+            write_null(out_json);
+            write_string(out_err, message.as_str());
+            status
+        }
+    }
+}
+
+unsafe fn resolve_on(
+    runtime: &Runtime,
+    did: *const c_char,
+    rpc_url: *const c_char,
+    accept: *const c_char,
+) -> Result<String, Failure> {
+    if read_str(accept) == Some(DID_CBOR) {
+        return Err((
+            ETHR_INVALID_ARGUMENT,
+            String::from(
+                "application/did+cbor is binary, use ethr_resolver_resolve_representation",
+            ),
+        ));
+    }
+
+    let representation = represent_on(runtime, did, rpc_url, accept)?;
+
+    match String::from_utf8(representation) {
+        Ok(val) => Ok(val),
+        Err(error) => Err((ETHR_ERROR, error.to_string())),
+    }
+}
+
+unsafe fn represent_on(
+    runtime: &Runtime,
+    did: *const c_char,
+    rpc_url: *const c_char,
+    accept: *const c_char,
+) -> Result<Vec<u8>, Failure> {
+    let (did, rpc_url, accept) = match (read_str(did), read_str(rpc_url), read_str(accept)) {
+        (Some(did), Some(rpc_url), Some(accept)) => (did, rpc_url, accept),
+        _ => {
+            return Err((
+                ETHR_INVALID_ARGUMENT,
+                String::from("did, rpc_url and accept must be non-null UTF-8 strings"),
+            ))
+        }
+    };
+
+    match runtime.block_on(resolve_representation(did, rpc_url, accept)) {
+        Ok(val) => Ok(val),
+        Err(error) => Err((ETHR_ERROR, error.to_string())),
+    }
+}
+
+unsafe fn read_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(value).to_str().ok()
+}
+
+unsafe fn write_null(out: *mut *mut c_char) {
+    if !out.is_null() {
+        *out = ptr::null_mut();
+    }
+}
+
+unsafe fn write_string(out: *mut *mut c_char, value: &str) {
+    if out.is_null() {
+        return;
+    }
+
+    *out = match CString::new(value.replace('\0', "")) {
+        Ok(val) => val.into_raw(),
+        Err(_error) => ptr::null_mut(),
+    };
+}
+
+unsafe fn write_bytes(out_bytes: *mut *mut u8, out_len: *mut usize, value: Option<Vec<u8>>) {
+    if out_bytes.is_null() || out_len.is_null() {
+        return;
+    }
+
+    (*out_bytes, *out_len) = match value {
+        Some(val) => {
+            let len = val.len();
+            (Box::into_raw(val.into_boxed_slice()) as *mut u8, len)
+        }
+        None => (ptr::null_mut(), 0),
+    };
+}
@@ -0,0 +1,128 @@
+use ethers::types::{Log, H160, U256};
+use fi_common::error::Error;
+use serde::Serialize;
+
+use crate::events::attribute_changed::DIDAttributeChanged;
+use crate::events::delegate_changed::DIDDelegateChanged;
+use crate::events::owner_changed::DIDOwnerChanged;
+use crate::events::DiDEthrChangeEvent;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventMetadata {
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub transaction_hash: Option<String>,
+    pub log_index: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all_fields = "camelCase")]
+pub enum RegistryEvent {
+    OwnerChanged {
+        metadata: EventMetadata,
+        identity: String,
+        owner: String,
+        previous_change: u64,
+    },
+    DelegateChanged {
+        metadata: EventMetadata,
+        identity: String,
+        delegate_type: String,
+        delegate: String,
+        valid_to: U256,
+        previous_change: u64,
+    },
+    AttributeChanged {
+        metadata: EventMetadata,
+        identity: String,
+        name: String,
+        value: String,
+        valid_to: U256,
+        previous_change: u64,
+    },
+}
+
+impl RegistryEvent {
+    pub fn from_log(log: Log, timestamp: Option<u64>) -> Result<RegistryEvent, Error> {
+        let metadata = EventMetadata {
+            block_number: match log.block_number {
+                Some(val) => val.as_u64(),
+                None => return Err(Error::new("Log is missing a block number")),
+            },
+            timestamp,
+            transaction_hash: log
+                .transaction_hash
+                .map(|hash| format!("0x{}", hex::encode(hash.0))),
+            log_index: log.log_index.map(|index| index.as_u64()),
+        };
+
+        let topics = log.topics.clone();
+
+        if DIDAttributeChanged::is_event_of(&topics) {
+            let event = DIDAttributeChanged::try_from(log)?;
+            Ok(RegistryEvent::AttributeChanged {
+                metadata,
+                identity: format_address(event.identity),
+                name: String::from_utf8_lossy(
+                    &event
+                        .name
+                        .into_iter()
+                        .filter(|x| *x != 0)
+                        .collect::<Vec<u8>>(),
+                )
+                .to_string(),
+                value: format!("0x{}", hex::encode(&event.value)),
+                valid_to: event.valid_to,
+                previous_change: event.previous_change.as_u64(),
+            })
+        } else if DIDDelegateChanged::is_event_of(&topics) {
+            let event = DIDDelegateChanged::try_from(log)?;
+            Ok(RegistryEvent::DelegateChanged {
+                metadata,
+                identity: format_address(event.identity),
+                delegate_type: String::from_utf8_lossy(
+                    &event
+                        .delegate_type
+                        .into_iter()
+                        .filter(|x| *x != 0)
+                        .collect::<Vec<u8>>(),
+                )
+                .to_string(),
+                delegate: format_address(event.delegate),
+                valid_to: event.valid_to,
+                previous_change: event.previous_change.as_u64(),
+            })
+        } else if DIDOwnerChanged::is_event_of(&topics) {
+            let event = DIDOwnerChanged::try_from(log)?;
+            Ok(RegistryEvent::OwnerChanged {
+                metadata,
+                identity: format_address(event.identity),
+                owner: format_address(event.owner),
+                previous_change: event.previous_change.as_u64(),
+            })
+        } else {
+            Err(Error::new("Topic can't be identified"))
+        }
+    }
+
+    pub fn metadata(&self) -> &EventMetadata {
+        match self {
+            RegistryEvent::OwnerChanged { metadata, .. } => metadata,
+            RegistryEvent::DelegateChanged { metadata, .. } => metadata,
+            RegistryEvent::AttributeChanged { metadata, .. } => metadata,
+        }
+    }
+
+    pub fn identity(&self) -> &str {
+        match self {
+            RegistryEvent::OwnerChanged { identity, .. } => identity,
+            RegistryEvent::DelegateChanged { identity, .. } => identity,
+            RegistryEvent::AttributeChanged { identity, .. } => identity,
+        }
+    }
+}
+
+fn format_address(address: H160) -> String {
+    format!("0x{}", hex::encode(address.0))
+}
@@ -0,0 +1,347 @@
+use ethers::abi::RawLog;
+use ethers::contract::EthEvent;
+use ethers::types::{Log, H160, U256};
+use fi_common::error::Error;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::ethr::{
+    build_did_doc_from_fetched_logs, get_event_previous_change, parse_address, DEFAULT_REGISTRY,
+};
+use crate::events::delegate_changed::DIDDelegateChanged;
+use crate::events::DiDEthrChangeEvent;
+use crate::events::{attribute_changed::DIDAttributeChanged, owner_changed::DIDOwnerChanged};
+use crate::profile::OutputProfile;
+use crate::representation::DID_JSON;
+use crate::resolution::DidResolutionResult;
+use crate::{get_identity_address, get_network, new_document, to_resolution_result};
+
+type OwnerChanges = Vec<((u64, u64), H160)>;
+type DelegateChanges = HashMap<(H160, String), ((u64, u64), U256)>;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddressRole {
+    Owner,
+    Delegate(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressLink {
+    pub did: String,
+    pub role: AddressRole,
+    pub valid_to: Option<U256>,
+    pub valid: bool,
+}
+
+#[derive(Clone)]
+pub struct RegistryIndex {
+    network: Option<String>,
+    chain_id: u64,
+    registry: H160,
+    profile: OutputProfile,
+    last_block: Option<u64>,
+    timestamp: Option<u64>,
+    logs: HashMap<H160, Vec<Log>>,
+    seen: HashSet<(H160, (u64, u64))>,
+    documents: HashMap<H160, Result<DidResolutionResult, String>>,
+    delegates: HashMap<H160, DelegateChanges>,
+    owners: HashMap<H160, OwnerChanges>,
+    skipped: usize,
+}
+
+impl RegistryIndex {
+    pub fn new(network: Option<&str>, chain_id: u64, profile: OutputProfile) -> RegistryIndex {
+        RegistryIndex {
+            network: network.filter(|val| *val != "mainnet").map(String::from),
+            chain_id,
+            registry: parse_address(DEFAULT_REGISTRY).unwrap_or_default(),
+            profile,
+            last_block: None,
+            timestamp: None,
+            logs: HashMap::new(),
+            seen: HashSet::new(),
+            documents: HashMap::new(),
+            delegates: HashMap::new(),
+            owners: HashMap::new(),
+            skipped: 0,
+        }
+    }
+
+    pub fn set_registry(&mut self, registry: H160) {
+        self.registry = registry;
+    }
+
+    pub fn last_block(&self) -> Option<u64> {
+        self.last_block
+    }
+
+    pub fn set_last_block(&mut self, block_number: u64) {
+        self.last_block = Some(block_number);
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Adds registry logs to the index and rebuilds the documents they touch.
+    /// Documents are materialized as of `timestamp`, so expired and revoked
+    /// delegates are left out. Moving the clock forward also rebuilds the
+    /// documents whose delegates expired in between.
+    pub fn ingest(&mut self, logs: Vec<Log>, timestamp: u64) -> Vec<String> {
+        let mut touched = HashSet::<H160>::new();
+
+        let previous = self.timestamp;
+        self.timestamp = Some(timestamp);
+
+        for (identity, delegates) in &self.delegates {
+            if delegates.values().any(|(_position, valid_to)| {
+                *valid_to <= U256::from(timestamp)
+                    && previous.is_none_or(|val| *valid_to > U256::from(val))
+            }) {
+                touched.insert(*identity);
+            }
+        }
+
+        for log in logs {
+            if log.address != self.registry || !is_registry_event(&log) || log.topics.len() < 2 {
+                continue;
+            }
+
+            if !decodes(&log) {
+                self.skipped += 1;
+                continue;
+            }
+
+            if let Some(block_number) = log.block_number {
+                self.last_block = Some(match self.last_block {
+                    Some(val) => val.max(block_number.as_u64()),
+                    None => block_number.as_u64(),
+                });
+            }
+
+            let identity = H160::from(log.topics[1]);
+            let position = (
+                log.block_number.map_or(0, |val| val.as_u64()),
+                log.log_index.map_or(0, |val| val.as_u64()),
+            );
+
+            if !self.seen.insert((identity, position)) {
+                continue;
+            }
+
+            if let Some(event) = decode::<DIDDelegateChanged>(&log) {
+                let delegate_type = String::from_utf8_lossy(
+                    &event
+                        .delegate_type
+                        .into_iter()
+                        .filter(|x| *x != 0)
+                        .collect::<Vec<u8>>(),
+                )
+                .to_string();
+
+                let delegates = self.delegates.entry(identity).or_default();
+                let key = (event.delegate, delegate_type);
+                match delegates.get(&key) {
+                    Some((latest, _valid_to)) if *latest > position => {}
+                    _ => {
+                        delegates.insert(key, (position, event.valid_to));
+                    }
+                }
+            }
+
+            if let Some(event) = decode::<DIDOwnerChanged>(&log) {
+                let owners = self.owners.entry(identity).or_default();
+                if !owners.contains(&(position, event.owner)) {
+                    owners.push((position, event.owner));
+                    owners.sort();
+                }
+            }
+
+            self.logs.entry(identity).or_default().push(log);
+            touched.insert(identity);
+        }
+
+        let mut updated = Vec::new();
+
+        for identity in touched {
+            let result = self.materialize(identity);
+            self.documents.insert(identity, result);
+            updated.push(self.did(identity));
+        }
+
+        updated.sort();
+        updated
+    }
+
+    pub fn document(&self, did: &str) -> Option<Result<DidResolutionResult, Error>> {
+        let identity = self.identity(did)?;
+
+        self.documents.get(&identity).map(|result| match result {
+            Ok(val) => Ok(val.clone()),
+            Err(error) => Err(Error::new(error.as_str())),
+        })
+    }
+
+    pub fn dids(&self) -> Vec<String> {
+        let mut dids = self
+            .documents
+            .keys()
+            .map(|identity| self.did(*identity))
+            .collect::<Vec<String>>();
+        dids.sort();
+        dids
+    }
+
+    pub fn dids_with_delegate(&self, delegate: &str, timestamp: u64) -> Result<Vec<String>, Error> {
+        let delegate = parse_address(delegate)?;
+
+        let mut dids = self
+            .delegates
+            .iter()
+            .filter(|(_identity, delegates)| {
+                delegates
+                    .iter()
+                    .any(|((address, _delegate_type), (_position, valid_to))| {
+                        *address == delegate && *valid_to > U256::from(timestamp)
+                    })
+            })
+            .map(|(identity, _delegates)| self.did(*identity))
+            .collect::<Vec<String>>();
+        dids.sort();
+
+        Ok(dids)
+    }
+
+    pub fn links(&self, address: &str, timestamp: u64) -> Result<Vec<AddressLink>, Error> {
+        let address = parse_address(address)?;
+        let mut links = Vec::new();
+
+        for (identity, owners) in &self.owners {
+            if owners.iter().any(|(_position, owner)| *owner == address) {
+                links.push(AddressLink {
+                    did: self.did(*identity),
+                    role: AddressRole::Owner,
+                    valid_to: None,
+                    valid: owners
+                        .last()
+                        .is_some_and(|(_position, owner)| *owner == address),
+                });
+            }
+        }
+
+        for (identity, delegates) in &self.delegates {
+            for ((delegate, delegate_type), (_position, valid_to)) in delegates {
+                if *delegate == address {
+                    links.push(AddressLink {
+                        did: self.did(*identity),
+                        role: AddressRole::Delegate(delegate_type.clone()),
+                        valid_to: Some(*valid_to),
+                        valid: *valid_to > U256::from(timestamp),
+                    });
+                }
+            }
+        }
+
+        links.sort_by(|a, b| (&a.did, &a.role).cmp(&(&b.did, &b.role)));
+        Ok(links)
+    }
+
+    fn materialize(&self, identity: H160) -> Result<DidResolutionResult, String> {
+        let did = self.did(identity);
+
+        let mut logs = match self.logs.get(&identity) {
+            Some(val) => val.clone(),
+            None => Vec::new(),
+        };
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut did_doc = match new_document(did.as_str(), DID_JSON) {
+            Ok(val) => val,
+            Err(error) => return Err(error.to_string()),
+        };
+
+        // Logs indexed from a later block than the identity's first change
+        // leave its earliest event pointing at a change the index never saw.
+        let partial = logs
+            .first()
+            .and_then(get_event_previous_change)
+            .is_some_and(|val| !val.is_zero());
+
+        let mut result = match build_did_doc_from_fetched_logs(
+            hex::encode(identity.0).as_str(),
+            &mut did_doc,
+            self.chain_id,
+            self.timestamp,
+            logs,
+            &self.profile,
+        ) {
+            Ok(val) => match to_resolution_result(DID_JSON, val, &self.profile) {
+                Ok(val) => val,
+                Err(error) => return Err(error.to_string()),
+            },
+            Err(error) => return Err(error.to_string()),
+        };
+
+        if partial {
+            result.did_document_metadata.partial = Some(true);
+        }
+
+        Ok(result)
+    }
+
+    fn did(&self, identity: H160) -> String {
+        match &self.network {
+            Some(network) => format!("did:ethr:{}:{:#x}", network, identity),
+            None => format!("did:ethr:{:#x}", identity),
+        }
+    }
+
+    fn identity(&self, did: &str) -> Option<H160> {
+        let network = match &self.network {
+            Some(val) => val.as_str(),
+            None => "mainnet",
+        };
+
+        if !did.starts_with("did:ethr:") || get_network(did) != network {
+            return None;
+        }
+
+        get_identity_address(did)
+            .ok()
+            .and_then(|val| parse_address(val.as_str()).ok())
+    }
+}
+
+pub fn references_address(log: &Log, address: H160) -> bool {
+    if let Some(event) = decode::<DIDOwnerChanged>(log) {
+        return event.owner == address;
+    }
+
+    if let Some(event) = decode::<DIDDelegateChanged>(log) {
+        return event.delegate == address;
+    }
+
+    false
+}
+
+fn decode<T: EthEvent + DiDEthrChangeEvent>(log: &Log) -> Option<T> {
+    if !T::is_event_of(&log.topics) {
+        return None;
+    }
+
+    T::decode_log(&RawLog::from(log.clone())).ok()
+}
+
+fn decodes(log: &Log) -> bool {
+    decode::<DIDOwnerChanged>(log).is_some()
+        || decode::<DIDDelegateChanged>(log).is_some()
+        || decode::<DIDAttributeChanged>(log).is_some_and(|event| !event.is_malformed())
+}
+
+fn is_registry_event(log: &Log) -> bool {
+    DIDOwnerChanged::is_event_of(&log.topics)
+        || DIDDelegateChanged::is_event_of(&log.topics)
+        || DIDAttributeChanged::is_event_of(&log.topics)
+}
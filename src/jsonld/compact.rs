@@ -0,0 +1,287 @@
+use fi_common::error::Error;
+use serde_json::{Map, Value};
+
+use super::context::{ActiveContext, TermDefinition};
+use super::expand::as_array;
+
+pub fn compact(expanded: &[Value], context: &Value) -> Result<Value, Error> {
+    let active = ActiveContext::default().process(context, false, true)?;
+
+    let mut nodes = Vec::new();
+    for node in expanded {
+        if let Value::Object(node) = node {
+            nodes.push(compact_node(&active, node)?);
+        }
+    }
+
+    let mut result = match nodes.len() {
+        1 => match nodes.pop() {
+            Some(Value::Object(val)) => val,
+            _ => Map::new(),
+        },
+        _ => {
+            let mut graph = Map::new();
+            graph.insert(String::from("@graph"), Value::Array(nodes));
+            graph
+        }
+    };
+
+    result.insert(String::from("@context"), context.clone());
+    Ok(Value::Object(result))
+}
+
+fn compact_node(active: &ActiveContext, node: &Map<String, Value>) -> Result<Value, Error> {
+    let types = match node.get("@type") {
+        Some(val) => as_array(val)
+            .iter()
+            .filter_map(|val| val.as_str().map(String::from))
+            .collect::<Vec<String>>(),
+        None => Vec::new(),
+    };
+
+    let mut compacted_types = types
+        .iter()
+        .map(|_type| compact_iri(active, _type, true))
+        .collect::<Vec<String>>();
+    compacted_types.sort();
+
+    let mut type_scoped = active.clone();
+    for _type in &compacted_types {
+        let context = active
+            .terms
+            .get(_type)
+            .and_then(|term| term.context.clone());
+
+        if let Some(context) = context {
+            type_scoped = type_scoped.process(&context, false, false)?;
+        }
+    }
+
+    let mut result = Map::new();
+
+    if let Some(id) = node.get("@id").and_then(|val| val.as_str()) {
+        result.insert(
+            keyword_alias(&type_scoped, "@id"),
+            Value::String(compact_iri(&type_scoped, id, false)),
+        );
+    }
+
+    if !types.is_empty() {
+        let types = types
+            .iter()
+            .map(|_type| Value::String(compact_iri(active, _type, true)))
+            .collect::<Vec<Value>>();
+
+        result.insert(
+            keyword_alias(&type_scoped, "@type"),
+            match types.len() {
+                1 => types[0].clone(),
+                _ => Value::Array(types),
+            },
+        );
+    }
+
+    let mut properties = node
+        .keys()
+        .filter(|key| !key.starts_with('@'))
+        .cloned()
+        .collect::<Vec<String>>();
+    properties.sort();
+
+    for property in properties {
+        let values = as_array(&node[property.as_str()]);
+        let (term, definition) = select_term(&type_scoped, &property, &values);
+
+        let nested = match definition.context.clone() {
+            Some(context) => active.process(&context, true, true)?,
+            None => active.clone(),
+        };
+
+        let mut compacted = Vec::new();
+        for value in &values {
+            compacted.push(compact_value(&nested, &definition, value)?);
+        }
+
+        let as_set = definition
+            .container
+            .iter()
+            .any(|val| val == "@set" || val == "@list");
+
+        result.insert(
+            term,
+            match (compacted.len(), as_set) {
+                (1, false) => compacted[0].clone(),
+                _ => Value::Array(compacted),
+            },
+        );
+    }
+
+    Ok(Value::Object(result))
+}
+
+fn compact_value(
+    active: &ActiveContext,
+    definition: &TermDefinition,
+    value: &Value,
+) -> Result<Value, Error> {
+    let object = match value.as_object() {
+        Some(val) => val,
+        None => return Ok(value.clone()),
+    };
+
+    if let Some(literal) = object.get("@value") {
+        let datatype = object.get("@type").and_then(|val| val.as_str());
+
+        if datatype == definition.type_mapping.as_deref()
+            && !object.contains_key("@language")
+            && (datatype.is_some() || !literal.is_string() || definition.type_mapping.is_none())
+        {
+            return Ok(literal.clone());
+        }
+
+        let mut result = Map::new();
+        for (key, val) in object {
+            result.insert(
+                keyword_alias(active, key),
+                match (key.as_str(), val.as_str()) {
+                    ("@type", Some(val)) => Value::String(compact_iri(active, val, true)),
+                    _ => val.clone(),
+                },
+            );
+        }
+        return Ok(Value::Object(result));
+    }
+
+    if let Some(list) = object.get("@list") {
+        let mut items = Vec::new();
+        for item in as_array(list) {
+            items.push(compact_value(active, definition, &item)?);
+        }
+
+        return Ok(
+            match definition.container.iter().any(|val| val == "@list") {
+                true => Value::Array(items),
+                false => {
+                    let mut result = Map::new();
+                    result.insert(String::from("@list"), Value::Array(items));
+                    Value::Object(result)
+                }
+            },
+        );
+    }
+
+    if let Some(graph) = object.get("@graph") {
+        let mut nodes = Vec::new();
+        for node in as_array(graph) {
+            if let Value::Object(node) = node {
+                nodes.push(compact_node(active, &node)?);
+            }
+        }
+
+        return Ok(match nodes.len() {
+            1 => nodes[0].clone(),
+            _ => Value::Array(nodes),
+        });
+    }
+
+    if object.len() == 1 {
+        if let Some(id) = object.get("@id").and_then(|val| val.as_str()) {
+            match definition.type_mapping.as_deref() {
+                Some("@id") => return Ok(Value::String(compact_iri(active, id, false))),
+                Some("@vocab") => return Ok(Value::String(compact_iri(active, id, true))),
+                _ => {}
+            };
+        }
+    }
+
+    compact_node(active, object)
+}
+
+fn select_term(active: &ActiveContext, iri: &str, values: &[Value]) -> (String, TermDefinition) {
+    let references = values.iter().all(|val| {
+        val.as_object()
+            .is_some_and(|val| val.len() == 1 && val.contains_key("@id"))
+    });
+
+    let mut candidates = active
+        .terms
+        .iter()
+        .filter(|(_term, definition)| definition.id.as_deref() == Some(iri))
+        .map(|(term, definition)| {
+            let matches_type = match definition.type_mapping.as_deref() {
+                Some("@id") | Some("@vocab") => references,
+                Some(_type) => values
+                    .iter()
+                    .all(|val| val.get("@type").and_then(|val| val.as_str()) == Some(_type)),
+                None => !references,
+            };
+            (!matches_type, term.len(), term.clone(), definition.clone())
+        })
+        .collect::<Vec<(bool, usize, String, TermDefinition)>>();
+    candidates.sort_by(|a, b| (a.0, a.1, &a.2).cmp(&(b.0, b.1, &b.2)));
+
+    match candidates.into_iter().next() {
+        Some((_mismatch, _length, term, definition)) => (term, definition),
+        None => (compact_iri(active, iri, true), TermDefinition::default()),
+    }
+}
+
+fn compact_iri(active: &ActiveContext, iri: &str, vocab: bool) -> String {
+    if vocab {
+        let mut terms = active
+            .terms
+            .iter()
+            .filter(|(_term, definition)| definition.id.as_deref() == Some(iri))
+            .map(|(term, _definition)| term.clone())
+            .collect::<Vec<String>>();
+        terms.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+
+        if let Some(term) = terms.into_iter().next() {
+            return term;
+        }
+
+        if let Some(suffix) = active
+            .vocab
+            .as_deref()
+            .and_then(|vocab| iri.strip_prefix(vocab))
+        {
+            if !suffix.is_empty() && !active.terms.contains_key(suffix) {
+                return String::from(suffix);
+            }
+        }
+    }
+
+    let mut prefixed = active
+        .terms
+        .iter()
+        .filter_map(|(term, definition)| {
+            let prefix = definition.id.as_deref()?;
+            let suffix = iri.strip_prefix(prefix)?;
+            match !suffix.is_empty() && prefix.ends_with(['/', '#', ':']) && !term.contains(':') {
+                true => Some(format!("{}:{}", term, suffix)),
+                false => None,
+            }
+        })
+        .collect::<Vec<String>>();
+    prefixed.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+
+    match prefixed.into_iter().next() {
+        Some(val) => val,
+        None => String::from(iri),
+    }
+}
+
+fn keyword_alias(active: &ActiveContext, keyword: &str) -> String {
+    let mut aliases = active
+        .terms
+        .iter()
+        .filter(|(_term, definition)| definition.id.as_deref() == Some(keyword))
+        .map(|(term, _definition)| term.clone())
+        .collect::<Vec<String>>();
+    aliases.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+
+    match aliases.into_iter().next() {
+        Some(val) => val,
+        None => String::from(keyword),
+    }
+}
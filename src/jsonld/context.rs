@@ -0,0 +1,408 @@
+use fi_common::error::Error;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use super::load_context;
+
+const MAX_CONTEXT_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TermDefinition {
+    pub id: Option<String>,
+    pub type_mapping: Option<String>,
+    pub container: Vec<String>,
+    pub context: Option<Value>,
+    pub protected: bool,
+}
+
+impl TermDefinition {
+    fn same_definition(&self, other: &TermDefinition) -> bool {
+        self.id == other.id
+            && self.type_mapping == other.type_mapping
+            && self.container == other.container
+            && self.context == other.context
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ActiveContext {
+    pub terms: HashMap<String, TermDefinition>,
+    pub vocab: Option<String>,
+    pub base: Option<String>,
+    pub previous: Option<Box<ActiveContext>>,
+}
+
+impl ActiveContext {
+    pub fn process(
+        &self,
+        local: &Value,
+        override_protected: bool,
+        propagate: bool,
+    ) -> Result<ActiveContext, Error> {
+        self.process_with_depth(local, override_protected, propagate, 0)
+    }
+
+    fn process_with_depth(
+        &self,
+        local: &Value,
+        override_protected: bool,
+        propagate: bool,
+        depth: usize,
+    ) -> Result<ActiveContext, Error> {
+        if depth > MAX_CONTEXT_DEPTH {
+            return Err(Error::new("JSON-LD context nesting is too deep"));
+        }
+
+        let mut result = self.clone();
+
+        let propagate = match local.get("@propagate") {
+            Some(Value::Bool(val)) => *val,
+            _ => propagate,
+        };
+
+        if !propagate && result.previous.is_none() {
+            result.previous = Some(Box::new(self.clone()));
+        }
+
+        let contexts = match local {
+            Value::Array(val) => val.clone(),
+            _ => vec![local.clone()],
+        };
+
+        for context in contexts {
+            match context {
+                Value::Null => {
+                    if !override_protected && result.terms.values().any(|term| term.protected) {
+                        return Err(Error::new("Invalid context nullification"));
+                    }
+
+                    result = ActiveContext {
+                        base: self.base.clone(),
+                        previous: match propagate {
+                            true => None,
+                            false => Some(Box::new(result.clone())),
+                        },
+                        ..ActiveContext::default()
+                    };
+                }
+                Value::String(url) => {
+                    let document = match load_context(url.as_str()) {
+                        Some(val) => val,
+                        None => {
+                            return Err(Error::new(
+                                format!("Unable to load JSON-LD context: {}", url).as_str(),
+                            ))
+                        }
+                    };
+
+                    let previous = result.previous.take();
+                    result = result.process_with_depth(
+                        &document["@context"],
+                        override_protected,
+                        true,
+                        depth + 1,
+                    )?;
+                    result.previous = previous;
+                }
+                Value::Object(definition) => {
+                    match result.apply_definition(&definition, override_protected) {
+                        Ok(_val) => {}
+                        Err(error) => return Err(error),
+                    };
+                }
+                _ => return Err(Error::new("Invalid JSON-LD local context")),
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn apply_definition(
+        &mut self,
+        local: &Map<String, Value>,
+        override_protected: bool,
+    ) -> Result<(), Error> {
+        if let Some(base) = local.get("@base") {
+            self.base = base.as_str().map(String::from);
+        }
+
+        if let Some(vocab) = local.get("@vocab") {
+            self.vocab = match vocab {
+                Value::String(val) => {
+                    let mut defined = HashMap::new();
+                    self.expand_iri_with(val, true, true, local, &mut defined, override_protected)?
+                }
+                _ => None,
+            };
+        }
+
+        let protected = local
+            .get("@protected")
+            .and_then(|val| val.as_bool())
+            .unwrap_or(false);
+
+        let mut defined: HashMap<String, bool> = HashMap::new();
+        for term in local.keys() {
+            if term.starts_with('@') {
+                continue;
+            }
+
+            match self.create_term_definition(
+                local,
+                term,
+                &mut defined,
+                protected,
+                override_protected,
+            ) {
+                Ok(_val) => {}
+                Err(error) => return Err(error),
+            };
+        }
+
+        Ok(())
+    }
+
+    fn create_term_definition(
+        &mut self,
+        local: &Map<String, Value>,
+        term: &str,
+        defined: &mut HashMap<String, bool>,
+        protected: bool,
+        override_protected: bool,
+    ) -> Result<(), Error> {
+        match defined.get(term) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                return Err(Error::new(
+                    format!("Cyclic IRI mapping for JSON-LD term: {}", term).as_str(),
+                ))
+            }
+            None => {}
+        };
+
+        defined.insert(String::from(term), false);
+
+        let previous = self.terms.remove(term);
+
+        let value = match local.get(term) {
+            Some(Value::String(val)) => {
+                let mut map = Map::new();
+                map.insert(String::from("@id"), Value::String(val.clone()));
+                map
+            }
+            Some(Value::Object(val)) => val.clone(),
+            Some(Value::Null) => {
+                let mut map = Map::new();
+                map.insert(String::from("@id"), Value::Null);
+                map
+            }
+            _ => {
+                return Err(Error::new(
+                    format!("Invalid JSON-LD term definition: {}", term).as_str(),
+                ))
+            }
+        };
+
+        let mut definition = TermDefinition {
+            protected: value
+                .get("@protected")
+                .and_then(|val| val.as_bool())
+                .unwrap_or(protected),
+            ..TermDefinition::default()
+        };
+
+        if let Some(type_mapping) = value.get("@type").and_then(|val| val.as_str()) {
+            definition.type_mapping = match type_mapping {
+                "@id" | "@vocab" | "@json" | "@none" => Some(String::from(type_mapping)),
+                _ => self.expand_iri_with(
+                    type_mapping,
+                    false,
+                    true,
+                    local,
+                    defined,
+                    override_protected,
+                )?,
+            };
+        }
+
+        match value.get("@id") {
+            Some(Value::Null) => definition.id = None,
+            Some(Value::String(id)) if id != term => {
+                definition.id =
+                    self.expand_iri_with(id, false, true, local, defined, override_protected)?;
+            }
+            _ => {
+                if let Some((prefix, suffix)) = term.split_once(':') {
+                    if local.contains_key(prefix) {
+                        match self.create_term_definition(
+                            local,
+                            prefix,
+                            defined,
+                            protected,
+                            override_protected,
+                        ) {
+                            Ok(_val) => {}
+                            Err(error) => return Err(error),
+                        };
+                    }
+
+                    definition.id = match self.terms.get(prefix).and_then(|val| val.id.clone()) {
+                        Some(prefix_iri) => Some(format!("{}{}", prefix_iri, suffix)),
+                        None => Some(String::from(term)),
+                    };
+                } else {
+                    definition.id = match self.vocab.clone() {
+                        Some(vocab) => Some(format!("{}{}", vocab, term)),
+                        None => {
+                            return Err(Error::new(
+                                format!("Invalid IRI mapping for JSON-LD term: {}", term).as_str(),
+                            ))
+                        }
+                    };
+                }
+            }
+        };
+
+        definition.container = match value.get("@container") {
+            Some(Value::String(val)) => vec![val.clone()],
+            Some(Value::Array(val)) => val
+                .iter()
+                .filter_map(|item| item.as_str().map(String::from))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        definition.context = value.get("@context").cloned();
+
+        if let Some(previous) = previous {
+            if previous.protected && !override_protected {
+                if !previous.same_definition(&definition) {
+                    return Err(Error::new(
+                        format!("Protected JSON-LD term redefinition: {}", term).as_str(),
+                    ));
+                }
+                definition = previous;
+            }
+        }
+
+        self.terms.insert(String::from(term), definition);
+        defined.insert(String::from(term), true);
+
+        Ok(())
+    }
+
+    fn expand_iri_with(
+        &mut self,
+        value: &str,
+        document_relative: bool,
+        vocab: bool,
+        local: &Map<String, Value>,
+        defined: &mut HashMap<String, bool>,
+        override_protected: bool,
+    ) -> Result<Option<String>, Error> {
+        let mut dependencies = vec![value];
+        if let Some((prefix, _suffix)) = value.split_once(':') {
+            dependencies.push(prefix);
+        }
+
+        for dependency in dependencies {
+            if local.contains_key(dependency) && !defined.get(dependency).is_some_and(|val| *val) {
+                let protected = local
+                    .get("@protected")
+                    .and_then(|val| val.as_bool())
+                    .unwrap_or(false);
+
+                match self.create_term_definition(
+                    local,
+                    dependency,
+                    defined,
+                    protected,
+                    override_protected,
+                ) {
+                    Ok(_val) => {}
+                    Err(error) => return Err(error),
+                };
+            }
+        }
+
+        Ok(self.expand_iri(value, document_relative, vocab))
+    }
+
+    pub fn expand_iri(&self, value: &str, document_relative: bool, vocab: bool) -> Option<String> {
+        if is_keyword(value) {
+            return Some(String::from(value));
+        }
+
+        if vocab {
+            if let Some(term) = self.terms.get(value) {
+                return term.id.clone();
+            }
+        }
+
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(String::from(value));
+            }
+
+            return match self.terms.get(prefix).and_then(|term| term.id.clone()) {
+                Some(prefix_iri) => Some(format!("{}{}", prefix_iri, suffix)),
+                None => Some(String::from(value)),
+            };
+        }
+
+        if vocab {
+            if let Some(vocab) = &self.vocab {
+                return Some(format!("{}{}", vocab, value));
+            }
+        }
+
+        if document_relative {
+            if let Some(base) = &self.base {
+                return Some(resolve_relative(base, value));
+            }
+        }
+
+        Some(String::from(value))
+    }
+}
+
+pub fn is_keyword(value: &str) -> bool {
+    matches!(
+        value,
+        "@base"
+            | "@container"
+            | "@context"
+            | "@direction"
+            | "@graph"
+            | "@id"
+            | "@import"
+            | "@included"
+            | "@index"
+            | "@json"
+            | "@language"
+            | "@list"
+            | "@nest"
+            | "@none"
+            | "@prefix"
+            | "@propagate"
+            | "@protected"
+            | "@reverse"
+            | "@set"
+            | "@type"
+            | "@value"
+            | "@version"
+            | "@vocab"
+    )
+}
+
+fn resolve_relative(base: &str, value: &str) -> String {
+    if value.starts_with('#') {
+        let base = base.split('#').next().unwrap_or(base);
+        return format!("{}{}", base, value);
+    }
+
+    match base.rfind('/') {
+        Some(index) => format!("{}{}", &base[..index + 1], value),
+        None => format!("{}{}", base, value),
+    }
+}
@@ -0,0 +1,316 @@
+use fi_common::error::Error;
+use serde_json::{Map, Value};
+
+use super::context::{is_keyword, ActiveContext};
+
+pub struct Expander {
+    pub undefined_terms: Vec<String>,
+}
+
+impl Expander {
+    pub fn new() -> Expander {
+        Expander {
+            undefined_terms: Vec::new(),
+        }
+    }
+
+    pub fn expand(&mut self, document: &Value) -> Result<Vec<Value>, Error> {
+        let expanded = self.expand_element(&ActiveContext::default(), None, document, false)?;
+
+        Ok(match expanded {
+            Value::Null => Vec::new(),
+            Value::Array(val) => val,
+            Value::Object(val) => {
+                if val.len() == 1 && val.contains_key("@graph") {
+                    as_array(&val["@graph"])
+                } else {
+                    vec![Value::Object(val)]
+                }
+            }
+            val => vec![val],
+        })
+    }
+
+    fn expand_element(
+        &mut self,
+        active: &ActiveContext,
+        active_property: Option<&str>,
+        element: &Value,
+        from_map: bool,
+    ) -> Result<Value, Error> {
+        match element {
+            Value::Null => Ok(Value::Null),
+            Value::Array(items) => {
+                let mut result = Vec::new();
+                for item in items {
+                    match self.expand_element(active, active_property, item, from_map) {
+                        Ok(Value::Null) => {}
+                        Ok(Value::Array(mut val)) => result.append(&mut val),
+                        Ok(val) => result.push(val),
+                        Err(error) => return Err(error),
+                    };
+                }
+                Ok(Value::Array(result))
+            }
+            Value::Object(object) => self.expand_object(active, active_property, object, from_map),
+            _ => match active_property {
+                None | Some("@graph") => Ok(Value::Null),
+                Some(property) => {
+                    let property_context = active
+                        .terms
+                        .get(property)
+                        .and_then(|term| term.context.clone());
+
+                    match property_context {
+                        Some(context) => match active.process(&context, true, true) {
+                            Ok(val) => Ok(expand_value(&val, property, element)),
+                            Err(error) => Err(error),
+                        },
+                        None => Ok(expand_value(active, property, element)),
+                    }
+                }
+            },
+        }
+    }
+
+    fn expand_object(
+        &mut self,
+        active: &ActiveContext,
+        active_property: Option<&str>,
+        object: &Map<String, Value>,
+        from_map: bool,
+    ) -> Result<Value, Error> {
+        let mut active = active.clone();
+
+        let property_context = active_property
+            .and_then(|property| active.terms.get(property))
+            .and_then(|term| term.context.clone());
+
+        if let Some(previous) = active.previous.clone() {
+            let keeps_context = from_map
+                || object
+                    .keys()
+                    .any(|key| active.expand_iri(key, false, true).as_deref() == Some("@value"))
+                || (object.len() == 1
+                    && object
+                        .keys()
+                        .all(|key| active.expand_iri(key, false, true).as_deref() == Some("@id")));
+
+            if !keeps_context {
+                active = *previous;
+            }
+        }
+
+        if let Some(context) = property_context {
+            active = active.process(&context, true, true)?;
+        }
+
+        if let Some(context) = object.get("@context") {
+            active = active.process(context, false, true)?;
+        }
+
+        let type_scoped_context = active.clone();
+
+        let mut keys = object.keys().cloned().collect::<Vec<String>>();
+        keys.sort();
+
+        let mut types = keys
+            .iter()
+            .filter(|key| active.expand_iri(key, false, true).as_deref() == Some("@type"))
+            .flat_map(|key| as_array(&object[key.as_str()]))
+            .filter_map(|val| val.as_str().map(String::from))
+            .collect::<Vec<String>>();
+        types.sort();
+
+        for _type in types {
+            let context = type_scoped_context
+                .terms
+                .get(&_type)
+                .and_then(|term| term.context.clone());
+
+            if let Some(context) = context {
+                active = active.process(&context, false, false)?;
+            }
+        }
+
+        let mut result: Map<String, Value> = Map::new();
+
+        for key in keys {
+            if key == "@context" {
+                continue;
+            }
+
+            let value = &object[key.as_str()];
+
+            let expanded_property = match active.expand_iri(&key, false, true) {
+                Some(val) if is_keyword(&val) || val.contains(':') => val,
+                _ => {
+                    self.undefined_terms.push(key.clone());
+                    continue;
+                }
+            };
+
+            if is_keyword(&expanded_property) {
+                let expanded_value = match expanded_property.as_str() {
+                    "@id" => match value.as_str() {
+                        Some(val) => match active.expand_iri(val, true, false) {
+                            Some(val) => Value::String(val),
+                            None => Value::Null,
+                        },
+                        None => return Err(Error::new("Invalid JSON-LD @id value")),
+                    },
+                    "@type" => {
+                        let mut expanded_types = Vec::new();
+                        for _type in as_array(value).iter().filter_map(|val| val.as_str()) {
+                            match type_scoped_context.expand_iri(_type, true, true) {
+                                Some(val) if val.contains(':') => {
+                                    expanded_types.push(Value::String(val))
+                                }
+                                _ => self.undefined_terms.push(String::from(_type)),
+                            };
+                        }
+                        Value::Array(expanded_types)
+                    }
+                    "@graph" => match self.expand_element(&active, Some("@graph"), value, false) {
+                        Ok(val) => Value::Array(as_array(&val)),
+                        Err(error) => return Err(error),
+                    },
+                    "@list" | "@set" => {
+                        match self.expand_element(&active, active_property, value, false) {
+                            Ok(val) => Value::Array(as_array(&val)),
+                            Err(error) => return Err(error),
+                        }
+                    }
+                    "@value" | "@index" | "@direction" => value.clone(),
+                    "@language" => match value.as_str() {
+                        Some(val) => Value::String(val.to_lowercase()),
+                        None => return Err(Error::new("Invalid JSON-LD @language value")),
+                    },
+                    _ => {
+                        return Err(Error::new(
+                            format!("Unsupported JSON-LD keyword: {}", expanded_property).as_str(),
+                        ))
+                    }
+                };
+
+                result.insert(expanded_property, expanded_value);
+                continue;
+            }
+
+            let definition = active.terms.get(&key).cloned().unwrap_or_default();
+
+            let mut expanded_value = match definition.type_mapping.as_deref() {
+                Some("@json") => {
+                    let mut json = Map::new();
+                    json.insert(String::from("@value"), value.clone());
+                    json.insert(String::from("@type"), Value::String(String::from("@json")));
+                    Value::Object(json)
+                }
+                _ => self.expand_element(&active, Some(&key), value, false)?,
+            };
+
+            if expanded_value.is_null() {
+                continue;
+            }
+
+            if definition.container.iter().any(|val| val == "@list")
+                && expanded_value.get("@list").is_none()
+            {
+                let mut list = Map::new();
+                list.insert(
+                    String::from("@list"),
+                    Value::Array(as_array(&expanded_value)),
+                );
+                expanded_value = Value::Object(list);
+            }
+
+            if definition.container.iter().any(|val| val == "@graph") {
+                expanded_value = Value::Array(
+                    as_array(&expanded_value)
+                        .into_iter()
+                        .map(|val| {
+                            let mut graph = Map::new();
+                            graph.insert(String::from("@graph"), Value::Array(as_array(&val)));
+                            Value::Object(graph)
+                        })
+                        .collect(),
+                );
+            }
+
+            let entry = result
+                .entry(expanded_property)
+                .or_insert(Value::Array(Vec::new()));
+            if let Value::Array(items) = entry {
+                items.append(&mut as_array(&expanded_value));
+            }
+        }
+
+        if result.contains_key("@value") {
+            if result["@value"].is_null() {
+                return Ok(Value::Null);
+            }
+
+            if let Some(Value::Array(types)) = result.get("@type").cloned() {
+                match types.first() {
+                    Some(val) => result.insert(String::from("@type"), val.clone()),
+                    None => result.remove("@type"),
+                };
+            }
+        } else if result.len() == 1 && result.contains_key("@language") {
+            return Ok(Value::Null);
+        }
+
+        if let Some(Value::Array(items)) = result.get("@set").cloned() {
+            return Ok(Value::Array(items));
+        }
+
+        if active_property.is_none() || active_property == Some("@graph") {
+            if result.is_empty() || result.contains_key("@value") || result.contains_key("@list") {
+                return Ok(Value::Null);
+            }
+
+            if result.len() == 1 && result.contains_key("@id") {
+                return Ok(Value::Null);
+            }
+        }
+
+        Ok(Value::Object(result))
+    }
+}
+
+fn expand_value(active: &ActiveContext, active_property: &str, value: &Value) -> Value {
+    let definition = active.terms.get(active_property);
+    let type_mapping = definition.and_then(|term| term.type_mapping.clone());
+
+    let mut result = Map::new();
+
+    match (type_mapping.as_deref(), value) {
+        (Some("@id"), Value::String(val)) => {
+            if let Some(iri) = active.expand_iri(val, true, false) {
+                result.insert(String::from("@id"), Value::String(iri));
+            }
+        }
+        (Some("@vocab"), Value::String(val)) => {
+            if let Some(iri) = active.expand_iri(val, true, true) {
+                result.insert(String::from("@id"), Value::String(iri));
+            }
+        }
+        (Some("@id"), _) | (Some("@vocab"), _) | (Some("@none"), _) | (None, _) => {
+            result.insert(String::from("@value"), value.clone());
+        }
+        (Some(_type), _) => {
+            result.insert(String::from("@value"), value.clone());
+            result.insert(String::from("@type"), Value::String(String::from(_type)));
+        }
+    };
+
+    Value::Object(result)
+}
+
+pub fn as_array(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(val) => val.clone(),
+        Value::Null => Vec::new(),
+        val => vec![val.clone()],
+    }
+}
@@ -0,0 +1,65 @@
+use fi_common::error::Error;
+use serde_json::Value;
+
+mod compact;
+mod context;
+mod expand;
+mod rdf;
+mod urdna2015;
+
+use expand::Expander;
+
+use crate::representation::{
+    DID_V1_CONTEXT, ED25519_2018_V1_CONTEXT, JWS_2020_V1_CONTEXT, MULTIKEY_V1_CONTEXT,
+    SECP256K1_2019_V1_CONTEXT, SECP256K1_RECOVERY_2020_V2_CONTEXT, SECURITY_V3_UNSTABLE_CONTEXT,
+    X25519_2019_V1_CONTEXT,
+};
+
+pub const CREDENTIALS_V1_URL: &str = "https://www.w3.org/2018/credentials/v1";
+
+pub fn load_context(url: &str) -> Option<Value> {
+    let document = match url {
+        CREDENTIALS_V1_URL => include_str!("contexts/credentials-v1.jsonld"),
+        DID_V1_CONTEXT => include_str!("contexts/did-v1.jsonld"),
+        SECP256K1_RECOVERY_2020_V2_CONTEXT => {
+            include_str!("contexts/secp256k1recovery-2020-v2.jsonld")
+        }
+        SECP256K1_2019_V1_CONTEXT => include_str!("contexts/secp256k1-2019-v1.jsonld"),
+        ED25519_2018_V1_CONTEXT => include_str!("contexts/ed25519-2018-v1.jsonld"),
+        X25519_2019_V1_CONTEXT => include_str!("contexts/x25519-2019-v1.jsonld"),
+        JWS_2020_V1_CONTEXT => include_str!("contexts/jws-2020-v1.jsonld"),
+        MULTIKEY_V1_CONTEXT => include_str!("contexts/multikey-v1.jsonld"),
+        SECURITY_V3_UNSTABLE_CONTEXT => include_str!("contexts/security-v3-unstable.jsonld"),
+        _ => return None,
+    };
+
+    serde_json::from_str(document).ok()
+}
+
+pub fn expand(document: &Value) -> Result<Vec<Value>, Error> {
+    Expander::new().expand(document)
+}
+
+pub fn compact(expanded: &[Value], context: &Value) -> Result<Value, Error> {
+    compact::compact(expanded, context)
+}
+
+pub fn undefined_terms(document: &Value) -> Result<Vec<String>, Error> {
+    let mut expander = Expander::new();
+    expander.expand(document)?;
+
+    let mut terms: Vec<String> = Vec::new();
+    for term in expander.undefined_terms {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+
+    Ok(terms)
+}
+
+pub fn canonicalize(document: &Value) -> Result<String, Error> {
+    let expanded = Expander::new().expand(document)?;
+
+    Ok(urdna2015::canonicalize(&rdf::to_rdf(&expanded)))
+}
@@ -0,0 +1,350 @@
+use serde_json::{Map, Value};
+
+use super::urdna2015::IdentifierIssuer;
+
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Term {
+    Iri(String),
+    Blank(String),
+    Literal {
+        value: String,
+        datatype: String,
+        language: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Quad {
+    pub subject: Term,
+    pub predicate: Term,
+    pub object: Term,
+    pub graph: Option<Term>,
+}
+
+impl Quad {
+    pub fn blank_nodes(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = Vec::new();
+        for term in [Some(&self.subject), Some(&self.object), self.graph.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            if let Term::Blank(id) = term {
+                if !result.contains(&id.as_str()) {
+                    result.push(id.as_str());
+                }
+            }
+        }
+        result
+    }
+
+    pub fn serialize(&self, label: &dyn Fn(&str) -> String) -> String {
+        let mut line = format!(
+            "{} {} {}",
+            serialize_term(&self.subject, label),
+            serialize_term(&self.predicate, label),
+            serialize_term(&self.object, label)
+        );
+
+        if let Some(graph) = &self.graph {
+            line.push(' ');
+            line.push_str(serialize_term(graph, label).as_str());
+        }
+
+        line.push_str(" .\n");
+        line
+    }
+}
+
+fn serialize_term(term: &Term, label: &dyn Fn(&str) -> String) -> String {
+    match term {
+        Term::Iri(iri) => format!("<{}>", iri),
+        Term::Blank(id) => label(id),
+        Term::Literal {
+            value,
+            datatype,
+            language,
+        } => {
+            let mut escaped = String::new();
+            for character in value.chars() {
+                match character {
+                    '\\' => escaped.push_str("\\\\"),
+                    '"' => escaped.push_str("\\\""),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    _ => escaped.push(character),
+                }
+            }
+
+            match language {
+                Some(language) => format!("\"{}\"@{}", escaped, language),
+                None if datatype == XSD_STRING => format!("\"{}\"", escaped),
+                None => format!("\"{}\"^^<{}>", escaped, datatype),
+            }
+        }
+    }
+}
+
+pub fn to_rdf(expanded: &[Value]) -> Vec<Quad> {
+    let mut quads = Vec::new();
+    let mut issuer = IdentifierIssuer::new("_:b");
+
+    for node in expanded {
+        if let Value::Object(node) = node {
+            node_to_rdf(node, None, &mut quads, &mut issuer);
+        }
+    }
+
+    quads.sort();
+    quads.dedup();
+    quads
+}
+
+fn node_to_rdf(
+    node: &Map<String, Value>,
+    graph: Option<&Term>,
+    quads: &mut Vec<Quad>,
+    issuer: &mut IdentifierIssuer,
+) -> Option<Term> {
+    let subject = match node.get("@id").and_then(|id| id.as_str()) {
+        Some(id) => to_resource(id, issuer)?,
+        None => Term::Blank(issuer.issue_new()),
+    };
+
+    if let Some(Value::Array(graph_nodes)) = node.get("@graph") {
+        for graph_node in graph_nodes {
+            if let Value::Object(graph_node) = graph_node {
+                node_to_rdf(graph_node, Some(&subject), quads, issuer);
+            }
+        }
+    }
+
+    if let Some(Value::Array(types)) = node.get("@type") {
+        for _type in types.iter().filter_map(|val| val.as_str()) {
+            if let Some(object) = to_resource(_type, issuer) {
+                quads.push(Quad {
+                    subject: subject.clone(),
+                    predicate: Term::Iri(String::from(RDF_TYPE)),
+                    object,
+                    graph: graph.cloned(),
+                });
+            }
+        }
+    }
+
+    let mut properties = node
+        .keys()
+        .filter(|key| !key.starts_with('@'))
+        .cloned()
+        .collect::<Vec<String>>();
+    properties.sort();
+
+    for property in properties {
+        if property.starts_with("_:") || !is_absolute_iri(&property) {
+            continue;
+        }
+
+        let values = match &node[property.as_str()] {
+            Value::Array(val) => val.clone(),
+            val => vec![val.clone()],
+        };
+
+        for value in values {
+            if let Some(object) = object_to_rdf(&value, graph, quads, issuer) {
+                quads.push(Quad {
+                    subject: subject.clone(),
+                    predicate: Term::Iri(property.clone()),
+                    object,
+                    graph: graph.cloned(),
+                });
+            }
+        }
+    }
+
+    Some(subject)
+}
+
+fn object_to_rdf(
+    item: &Value,
+    graph: Option<&Term>,
+    quads: &mut Vec<Quad>,
+    issuer: &mut IdentifierIssuer,
+) -> Option<Term> {
+    let item = item.as_object()?;
+
+    if item.contains_key("@value") {
+        return value_to_rdf(item);
+    }
+
+    if let Some(Value::Array(list)) = item.get("@list") {
+        return list_to_rdf(list, graph, quads, issuer);
+    }
+
+    node_to_rdf(item, graph, quads, issuer)
+}
+
+fn value_to_rdf(item: &Map<String, Value>) -> Option<Term> {
+    let value = &item["@value"];
+    let datatype = item.get("@type").and_then(|val| val.as_str());
+    let language = item
+        .get("@language")
+        .and_then(|val| val.as_str())
+        .map(String::from);
+
+    if datatype == Some("@json") {
+        return Some(Term::Literal {
+            value: canonical_json(value),
+            datatype: String::from(RDF_JSON),
+            language: None,
+        });
+    }
+
+    let (lexical, default_datatype) = match value {
+        Value::Bool(val) => (val.to_string(), XSD_BOOLEAN),
+        Value::Number(number) => {
+            let is_double = number.is_f64()
+                && number
+                    .as_f64()
+                    .is_some_and(|val| val.fract() != 0.0 || val.abs() >= 1e21);
+            if is_double || datatype == Some(XSD_DOUBLE) {
+                (canonical_double(number.as_f64()?), XSD_DOUBLE)
+            } else {
+                match number.as_f64() {
+                    Some(val) if number.is_f64() => (format!("{}", val as i64), XSD_INTEGER),
+                    _ => (number.to_string(), XSD_INTEGER),
+                }
+            }
+        }
+        Value::String(val) => (
+            val.clone(),
+            match language {
+                Some(_) => RDF_LANG_STRING,
+                None => XSD_STRING,
+            },
+        ),
+        _ => return None,
+    };
+
+    Some(Term::Literal {
+        value: lexical,
+        datatype: String::from(datatype.unwrap_or(default_datatype)),
+        language: match value.is_string() && datatype.is_none() {
+            true => language,
+            false => None,
+        },
+    })
+}
+
+fn list_to_rdf(
+    list: &[Value],
+    graph: Option<&Term>,
+    quads: &mut Vec<Quad>,
+    issuer: &mut IdentifierIssuer,
+) -> Option<Term> {
+    if list.is_empty() {
+        return Some(Term::Iri(String::from(RDF_NIL)));
+    }
+
+    let nodes = list
+        .iter()
+        .map(|_item| Term::Blank(issuer.issue_new()))
+        .collect::<Vec<Term>>();
+
+    for (index, item) in list.iter().enumerate() {
+        let subject = nodes[index].clone();
+
+        if let Some(object) = object_to_rdf(item, graph, quads, issuer) {
+            quads.push(Quad {
+                subject: subject.clone(),
+                predicate: Term::Iri(String::from(RDF_FIRST)),
+                object,
+                graph: graph.cloned(),
+            });
+        }
+
+        quads.push(Quad {
+            subject,
+            predicate: Term::Iri(String::from(RDF_REST)),
+            object: match nodes.get(index + 1) {
+                Some(val) => val.clone(),
+                None => Term::Iri(String::from(RDF_NIL)),
+            },
+            graph: graph.cloned(),
+        });
+    }
+
+    nodes.first().cloned()
+}
+
+fn to_resource(id: &str, issuer: &mut IdentifierIssuer) -> Option<Term> {
+    if id.starts_with("_:") {
+        return Some(Term::Blank(issuer.issue(&format!("input{}", id))));
+    }
+
+    match is_absolute_iri(id) {
+        true => Some(Term::Iri(String::from(id))),
+        false => None,
+    }
+}
+
+pub fn is_absolute_iri(value: &str) -> bool {
+    match value.split_once(':') {
+        Some((scheme, _rest)) => {
+            scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character))
+        }
+        None => false,
+    }
+}
+
+fn canonical_double(value: f64) -> String {
+    let formatted = format!("{:E}", value);
+    match formatted.split_once('E') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{}.0E{}", mantissa, exponent)
+        }
+        _ => formatted,
+    }
+}
+
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys = map.keys().collect::<Vec<&String>>();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+
+            let entries = keys
+                .iter()
+                .map(|key| {
+                    format!(
+                        "{}:{}",
+                        Value::String((*key).clone()),
+                        canonical_json(&map[key.as_str()])
+                    )
+                })
+                .collect::<Vec<String>>();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(canonical_json)
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        val => val.to_string(),
+    }
+}
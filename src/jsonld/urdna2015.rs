@@ -0,0 +1,289 @@
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+use super::rdf::{Quad, Term};
+
+#[derive(Debug, Clone)]
+pub struct IdentifierIssuer {
+    prefix: String,
+    counter: u64,
+    issued: Vec<String>,
+    identifiers: HashMap<String, String>,
+}
+
+impl IdentifierIssuer {
+    pub fn new(prefix: &str) -> IdentifierIssuer {
+        IdentifierIssuer {
+            prefix: String::from(prefix),
+            counter: 0,
+            issued: Vec::new(),
+            identifiers: HashMap::new(),
+        }
+    }
+
+    pub fn issue(&mut self, existing: &str) -> String {
+        if let Some(val) = self.identifiers.get(existing) {
+            return val.clone();
+        }
+
+        let identifier = self.issue_new();
+        self.issued.push(String::from(existing));
+        self.identifiers
+            .insert(String::from(existing), identifier.clone());
+        identifier
+    }
+
+    pub fn issue_new(&mut self) -> String {
+        let identifier = format!("{}{}", self.prefix, self.counter);
+        self.counter += 1;
+        identifier
+    }
+
+    pub fn get(&self, existing: &str) -> Option<&String> {
+        self.identifiers.get(existing)
+    }
+}
+
+struct Canonicalizer<'a> {
+    quads: &'a [Quad],
+    blank_node_quads: HashMap<String, Vec<usize>>,
+    canonical_issuer: IdentifierIssuer,
+}
+
+pub fn canonicalize(quads: &[Quad]) -> String {
+    let mut quads = quads.to_vec();
+    quads.sort();
+    quads.dedup();
+
+    let mut blank_node_quads: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut blank_nodes: Vec<String> = Vec::new();
+
+    for (index, quad) in quads.iter().enumerate() {
+        for blank_node in quad.blank_nodes() {
+            let entry = blank_node_quads
+                .entry(String::from(blank_node))
+                .or_default();
+            if entry.is_empty() {
+                blank_nodes.push(String::from(blank_node));
+            }
+            entry.push(index);
+        }
+    }
+
+    let mut canonicalizer = Canonicalizer {
+        quads: &quads,
+        blank_node_quads,
+        canonical_issuer: IdentifierIssuer::new("_:c14n"),
+    };
+
+    let mut hash_to_blank_nodes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for blank_node in &blank_nodes {
+        hash_to_blank_nodes
+            .entry(canonicalizer.hash_first_degree(blank_node))
+            .or_default()
+            .push(blank_node.clone());
+    }
+
+    let mut shared_hashes: Vec<Vec<String>> = Vec::new();
+    for (_hash, identifiers) in hash_to_blank_nodes {
+        match identifiers.len() {
+            1 => {
+                canonicalizer.canonical_issuer.issue(&identifiers[0]);
+            }
+            _ => shared_hashes.push(identifiers),
+        }
+    }
+
+    for identifiers in shared_hashes {
+        let mut hash_path_list: Vec<(String, IdentifierIssuer)> = Vec::new();
+
+        for identifier in identifiers {
+            if canonicalizer.canonical_issuer.get(&identifier).is_some() {
+                continue;
+            }
+
+            let mut issuer = IdentifierIssuer::new("_:b");
+            issuer.issue(&identifier);
+            hash_path_list.push(canonicalizer.hash_n_degree(&identifier, issuer));
+        }
+
+        hash_path_list.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (_hash, issuer) in hash_path_list {
+            for existing in &issuer.issued {
+                canonicalizer.canonical_issuer.issue(existing);
+            }
+        }
+    }
+
+    let canonical_issuer = &canonicalizer.canonical_issuer;
+    let label = |id: &str| match canonical_issuer.get(id) {
+        Some(val) => val.clone(),
+        None => String::from(id),
+    };
+
+    let mut lines = quads
+        .iter()
+        .map(|quad| quad.serialize(&label))
+        .collect::<Vec<String>>();
+    lines.sort();
+    lines.dedup();
+    lines.concat()
+}
+
+impl Canonicalizer<'_> {
+    fn hash_first_degree(&self, reference: &str) -> String {
+        let mut nquads = match self.blank_node_quads.get(reference) {
+            Some(indexes) => indexes
+                .iter()
+                .map(|index| {
+                    self.quads[*index].serialize(&|id: &str| match id == reference {
+                        true => String::from("_:a"),
+                        false => String::from("_:z"),
+                    })
+                })
+                .collect::<Vec<String>>(),
+            None => Vec::new(),
+        };
+        nquads.sort();
+
+        sha256_hex(nquads.concat().as_str())
+    }
+
+    fn hash_related_blank_node(
+        &self,
+        related: &str,
+        quad: &Quad,
+        issuer: &IdentifierIssuer,
+        position: &str,
+    ) -> String {
+        let identifier = match self.canonical_issuer.get(related) {
+            Some(val) => val.clone(),
+            None => match issuer.get(related) {
+                Some(val) => val.clone(),
+                None => self.hash_first_degree(related),
+            },
+        };
+
+        let mut input = String::from(position);
+        if position != "g" {
+            if let Term::Iri(predicate) = &quad.predicate {
+                input.push_str(format!("<{}>", predicate).as_str());
+            }
+        }
+        input.push_str(identifier.as_str());
+
+        sha256_hex(input.as_str())
+    }
+
+    fn hash_n_degree(
+        &self,
+        identifier: &str,
+        issuer: IdentifierIssuer,
+    ) -> (String, IdentifierIssuer) {
+        let mut issuer = issuer;
+        let mut hash_to_related: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        if let Some(indexes) = self.blank_node_quads.get(identifier) {
+            for index in indexes {
+                let quad = &self.quads[*index];
+
+                for (term, position) in [
+                    (Some(&quad.subject), "s"),
+                    (Some(&quad.object), "o"),
+                    (quad.graph.as_ref(), "g"),
+                ] {
+                    if let Some(Term::Blank(related)) = term {
+                        if related != identifier {
+                            let hash =
+                                self.hash_related_blank_node(related, quad, &issuer, position);
+                            hash_to_related
+                                .entry(hash)
+                                .or_default()
+                                .push(related.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut data_to_hash = String::new();
+
+        for (related_hash, blank_nodes) in hash_to_related {
+            data_to_hash.push_str(related_hash.as_str());
+
+            let mut chosen_path = String::new();
+            let mut chosen_issuer: Option<IdentifierIssuer> = None;
+
+            'permutations: for permutation in permutations(&blank_nodes) {
+                let mut issuer_copy = issuer.clone();
+                let mut path = String::new();
+                let mut recursion_list: Vec<String> = Vec::new();
+
+                for related in &permutation {
+                    match self.canonical_issuer.get(related) {
+                        Some(val) => path.push_str(val),
+                        None => {
+                            if issuer_copy.get(related).is_none() {
+                                recursion_list.push(related.clone());
+                            }
+                            path.push_str(issuer_copy.issue(related).as_str());
+                        }
+                    };
+
+                    if is_worse_path(&path, &chosen_path) {
+                        continue 'permutations;
+                    }
+                }
+
+                for related in recursion_list {
+                    let (result_hash, result_issuer) = self.hash_n_degree(&related, issuer_copy);
+                    issuer_copy = result_issuer;
+                    path.push_str(issuer_copy.issue(&related).as_str());
+                    path.push_str(format!("<{}>", result_hash).as_str());
+
+                    if is_worse_path(&path, &chosen_path) {
+                        continue 'permutations;
+                    }
+                }
+
+                if chosen_path.is_empty() || path < chosen_path {
+                    chosen_path = path;
+                    chosen_issuer = Some(issuer_copy);
+                }
+            }
+
+            data_to_hash.push_str(chosen_path.as_str());
+            if let Some(val) = chosen_issuer {
+                issuer = val;
+            }
+        }
+
+        (sha256_hex(data_to_hash.as_str()), issuer)
+    }
+}
+
+fn is_worse_path(path: &str, chosen_path: &str) -> bool {
+    !chosen_path.is_empty() && path.len() >= chosen_path.len() && path > chosen_path
+}
+
+fn permutations(items: &[String]) -> Vec<Vec<String>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+
+    let mut result = Vec::new();
+    for index in 0..items.len() {
+        let mut rest = items.to_vec();
+        let item = rest.remove(index);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, item.clone());
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+fn sha256_hex(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
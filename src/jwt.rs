@@ -0,0 +1,195 @@
+use base64::Engine;
+use fi_common::error::Error;
+use instant::SystemTime;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::resolve_with_metadata;
+use crate::signature::{
+    candidate_methods, normalize_recovery_id, verify_with_method, ProofPurpose,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JwtVerification {
+    pub issuer: String,
+    pub verification_method: String,
+    pub header: Value,
+    pub payload: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtValidation {
+    pub clock_skew: Duration,
+    pub audience: Option<String>,
+    pub now: Option<u64>,
+    /// Also reject tokens whose `iat` lies in the future. `nbf` is always enforced.
+    pub reject_future_iat: bool,
+}
+
+impl Default for JwtValidation {
+    fn default() -> Self {
+        JwtValidation {
+            clock_skew: Duration::from_secs(300),
+            audience: None,
+            now: None,
+            reject_future_iat: false,
+        }
+    }
+}
+
+pub async fn verify_jwt(
+    jwt: &str,
+    purpose: ProofPurpose,
+    provider: &str,
+    validation: &JwtValidation,
+) -> Result<JwtVerification, Error> {
+    let parts = jwt.split('.').collect::<Vec<&str>>();
+    if parts.len() != 3 {
+        return Err(Error::new("JWT must have three segments"));
+    }
+
+    let header = match decode_segment(parts[0]) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+    let payload = match decode_segment(parts[1]) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let signature = match base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(parts[2]) {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    let (signature, recovery_id) = match header["alg"].as_str() {
+        Some("ES256K") if signature.len() == 64 => (signature, None),
+        Some("ES256K-R") if signature.len() == 65 => match normalize_recovery_id(signature[64]) {
+            Some(val) => (signature[..64].to_vec(), Some(val)),
+            None => return Err(Error::new("Invalid recovery id in ES256K-R signature")),
+        },
+        Some("ES256K") | Some("ES256K-R") => {
+            return Err(Error::new("Invalid signature length for JWT algorithm"))
+        }
+        Some(alg) => {
+            return Err(Error::new(
+                format!("Unsupported JWT algorithm: {}", alg).as_str(),
+            ))
+        }
+        None => return Err(Error::new("JWT header is missing 'alg'")),
+    };
+
+    match validate_claims(&payload, validation) {
+        Ok(()) => {}
+        Err(error) => return Err(error),
+    };
+
+    let kid = header["kid"].as_str();
+
+    let issuer = match payload["iss"].as_str().or(kid) {
+        Some(val) => String::from(val.split('#').next().unwrap_or(val)),
+        None => return Err(Error::new("JWT has no 'iss' claim or 'kid' header")),
+    };
+
+    let did_doc = match resolve_with_metadata(issuer.as_str(), provider, "application/did+json")
+        .await
+        .and_then(|val| val.document())
+    {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let digest: [u8; 32] = Sha256::digest(format!("{}.{}", parts[0], parts[1]).as_bytes()).into();
+
+    for method in candidate_methods(&did_doc, purpose, kid) {
+        if verify_with_method(&method, digest, &signature, recovery_id) {
+            return Ok(JwtVerification {
+                issuer,
+                verification_method: String::from(method["id"].as_str().unwrap_or("")),
+                header,
+                payload,
+            });
+        }
+    }
+
+    Err(Error::new(
+        format!(
+            "No {} verification method of {} matches the JWT signature",
+            purpose.relationship(),
+            issuer
+        )
+        .as_str(),
+    ))
+}
+
+fn decode_segment(segment: &str) -> Result<Value, Error> {
+    let bytes = match base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(segment) {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(val) => Ok(val),
+        Err(error) => Err(Error::new(error.to_string().as_str())),
+    }
+}
+
+fn validate_claims(payload: &Value, validation: &JwtValidation) -> Result<(), Error> {
+    let now = match validation.now {
+        Some(val) => val,
+        None => match SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+        {
+            Some(val) => val.as_secs(),
+            None => return Err(Error::new("The system clock is before the Unix epoch")),
+        },
+    };
+    let skew = validation.clock_skew.as_secs();
+
+    if numeric_claim(payload, "nbf")?.is_some_and(|val| val > now.saturating_add(skew)) {
+        return Err(Error::new("JWT is not valid yet"));
+    }
+
+    let issued_at = numeric_claim(payload, "iat")?;
+    if validation.reject_future_iat && issued_at.is_some_and(|val| val > now.saturating_add(skew)) {
+        return Err(Error::new("JWT was issued in the future"));
+    }
+
+    if numeric_claim(payload, "exp")?.is_some_and(|val| val <= now.saturating_sub(skew)) {
+        return Err(Error::new("JWT has expired"));
+    }
+
+    let audiences = match &payload["aud"] {
+        Value::Null => Vec::new(),
+        Value::String(val) => vec![val.as_str()],
+        Value::Array(val) => val.iter().filter_map(|val| val.as_str()).collect(),
+        _ => return Err(Error::new("JWT claim 'aud' must be a string or an array")),
+    };
+
+    match &validation.audience {
+        Some(audience) if !audiences.contains(&audience.as_str()) => Err(Error::new(
+            format!("JWT audience does not include {}", audience).as_str(),
+        )),
+        None if !audiences.is_empty() => Err(Error::new(
+            "JWT has an 'aud' claim but no audience was expected",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn numeric_claim(payload: &Value, claim: &str) -> Result<Option<u64>, Error> {
+    match &payload[claim] {
+        Value::Null => Ok(None),
+        Value::Number(val) => match val.as_u64().or(val.as_f64().map(|val| val as u64)) {
+            Some(val) => Ok(Some(val)),
+            None => Err(Error::new(
+                format!("JWT claim '{}' must be a NumericDate", claim).as_str(),
+            )),
+        },
+        _ => Err(Error::new(
+            format!("JWT claim '{}' must be a NumericDate", claim).as_str(),
+        )),
+    }
+}
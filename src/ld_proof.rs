@@ -0,0 +1,208 @@
+use base64::Engine;
+use fi_common::error::Error;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::jsonld::{canonicalize, undefined_terms};
+use crate::resolve_with_metadata;
+use crate::signature::{
+    candidate_methods, normalize_recovery_id, verify_with_method, ProofPurpose,
+};
+
+pub const ECDSA_SECP256K1_SIGNATURE2019: &str = "EcdsaSecp256k1Signature2019";
+pub const ECDSA_SECP256K1_RECOVERY_SIGNATURE2020: &str = "EcdsaSecp256k1RecoverySignature2020";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdProofVerification {
+    pub proof_type: String,
+    pub proof_purpose: String,
+    pub verification_method: String,
+}
+
+pub async fn verify_ld_proof(
+    document: &Value,
+    provider: &str,
+) -> Result<Vec<LdProofVerification>, Error> {
+    let proofs = match document.get("proof") {
+        Some(Value::Array(val)) => val.clone(),
+        Some(Value::Object(val)) => vec![Value::Object(val.clone())],
+        _ => return Err(Error::new("Document has no proof")),
+    };
+
+    let mut unsigned = document.clone();
+    if let Some(val) = unsigned.as_object_mut() {
+        val.remove("proof");
+    }
+
+    check_terms(&unsigned)?;
+
+    let document_hash = match canonicalize(&unsigned) {
+        Ok(val) => Sha256::digest(val.as_bytes()),
+        Err(error) => return Err(error),
+    };
+
+    let mut verifications = Vec::new();
+
+    for proof in proofs {
+        match verify_proof(&proof, &document["@context"], &document_hash, provider).await {
+            Ok(val) => verifications.push(val),
+            Err(error) => return Err(error),
+        };
+    }
+
+    Ok(verifications)
+}
+
+async fn verify_proof(
+    proof: &Value,
+    context: &Value,
+    document_hash: &[u8],
+    provider: &str,
+) -> Result<LdProofVerification, Error> {
+    let proof_type = match proof["type"].as_str() {
+        Some(val) => String::from(val),
+        None => return Err(Error::new("Proof is missing 'type'")),
+    };
+
+    let expected_alg = match proof_type.as_str() {
+        ECDSA_SECP256K1_SIGNATURE2019 => "ES256K",
+        ECDSA_SECP256K1_RECOVERY_SIGNATURE2020 => "ES256K-R",
+        _ => {
+            return Err(Error::new(
+                format!("Unsupported proof type: {}", proof_type).as_str(),
+            ))
+        }
+    };
+
+    let verification_method = match proof["verificationMethod"].as_str() {
+        Some(val) => String::from(val),
+        None => return Err(Error::new("Proof is missing 'verificationMethod'")),
+    };
+
+    let proof_purpose = match proof["proofPurpose"].as_str() {
+        Some("authentication") => ProofPurpose::Authentication,
+        Some("assertionMethod") | None => ProofPurpose::AssertionMethod,
+        Some(val) => {
+            return Err(Error::new(
+                format!("Unsupported proof purpose: {}", val).as_str(),
+            ))
+        }
+    };
+
+    let jws = match proof["jws"].as_str() {
+        Some(val) => val,
+        None => return Err(Error::new("Proof is missing 'jws'")),
+    };
+
+    let parts = jws.split('.').collect::<Vec<&str>>();
+    if parts.len() != 3 || !parts[1].is_empty() {
+        return Err(Error::new("Proof 'jws' must be a detached JWS"));
+    }
+
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+    let header = match engine.decode(parts[0]) {
+        Ok(val) => match serde_json::from_slice::<Value>(&val) {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        },
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    if header["alg"].as_str() != Some(expected_alg) {
+        return Err(Error::new(
+            format!(
+                "{} proofs must use the {} algorithm",
+                proof_type, expected_alg
+            )
+            .as_str(),
+        ));
+    }
+
+    let signature = match engine.decode(parts[2]) {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    let (signature, recovery_id) = match (expected_alg, signature.len()) {
+        ("ES256K", 64) => (signature, None),
+        ("ES256K-R", 65) => match normalize_recovery_id(signature[64]) {
+            Some(val) => (signature[..64].to_vec(), Some(val)),
+            None => return Err(Error::new("Invalid recovery id in proof signature")),
+        },
+        _ => return Err(Error::new("Invalid proof signature length")),
+    };
+
+    let mut options = proof.clone();
+    if let Some(val) = options.as_object_mut() {
+        val.remove("jws");
+        val.remove("proofValue");
+        val.remove("signatureValue");
+        val.insert(String::from("@context"), context.clone());
+    }
+
+    check_terms(&options)?;
+
+    let options_hash = match canonicalize(&options) {
+        Ok(val) => Sha256::digest(val.as_bytes()),
+        Err(error) => return Err(error),
+    };
+
+    let mut verify_data = options_hash.to_vec();
+    verify_data.extend_from_slice(document_hash);
+
+    let mut signing_input = format!("{}.", parts[0]).into_bytes();
+    match header["b64"].as_bool() {
+        Some(false) => signing_input.extend(verify_data),
+        _ => signing_input.extend(engine.encode(verify_data).into_bytes()),
+    };
+
+    let digest: [u8; 32] = Sha256::digest(&signing_input).into();
+
+    let did = verification_method
+        .split('#')
+        .next()
+        .unwrap_or(verification_method.as_str());
+
+    let did_doc = match resolve_with_metadata(did, provider, "application/did+json")
+        .await
+        .and_then(|val| val.document())
+    {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    for method in candidate_methods(&did_doc, proof_purpose, Some(verification_method.as_str())) {
+        if verify_with_method(&method, digest, &signature, recovery_id) {
+            return Ok(LdProofVerification {
+                proof_type,
+                proof_purpose: String::from(proof_purpose.relationship()),
+                verification_method,
+            });
+        }
+    }
+
+    Err(Error::new(
+        format!(
+            "Proof signature does not match {} for {}",
+            verification_method,
+            proof_purpose.relationship()
+        )
+        .as_str(),
+    ))
+}
+
+fn check_terms(document: &Value) -> Result<(), Error> {
+    let terms = undefined_terms(document)?;
+
+    match terms.is_empty() {
+        true => Ok(()),
+        false => Err(Error::new(
+            format!(
+                "The signed document uses undefined JSON-LD terms: {}",
+                terms.join(", ")
+            )
+            .as_str(),
+        )),
+    }
+}
@@ -1,56 +1,204 @@
-use ethr::build_did_doc_from_logs;
+#[cfg(feature = "native")]
+use ethers::types::Address;
+use ethers::types::Log;
+use ethr::{build_did_doc_from_fetched_logs, build_did_doc_from_logs, get_history};
+#[cfg(feature = "native")]
+use ethr::{parse_address, DEFAULT_REGISTRY};
 use fi_common::{did::DidDocument, error::Error};
+#[cfg(feature = "native")]
+use futures::Stream;
+use profile::apply_output_profile;
 use regex::Regex;
-use util::strip0x;
+use representation::{document_context, is_supported};
+use serde_json::Value;
+use std::sync::OnceLock;
+use util::{public_key_hex_to_address, strip0x};
+#[cfg(feature = "native")]
+use watch::watch_registry;
 
+mod caip;
 mod did;
+mod eth_sign;
 mod ethr;
 mod events;
+#[cfg(feature = "native")]
+mod ffi;
+mod history;
+mod indexer;
+mod jsonld;
+mod jwt;
+mod ld_proof;
+mod profile;
+mod proof;
+#[cfg(feature = "python")]
+mod python;
+mod representation;
+mod resolution;
+mod resolver;
+mod router;
+mod signature;
+mod transport;
 mod util;
 mod verification;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "native")]
+mod watch;
+
+pub use caip::{
+    ethr_to_pkh, known_chain_id, pkh_document, pkh_to_ethr, AccountId, ChainId, EIP155,
+};
+pub use did::DidDoc;
+pub use eth_sign::{verify_personal_message, verify_typed_data};
+pub use ethr::verify_change_chain;
+pub use events::attribute_handler::{AttributeChange, AttributeHandler, AttributeHandlers};
+pub use events::{
+    attribute_changed::DIDAttributeChanged, delegate_changed::DIDDelegateChanged,
+    owner_changed::DIDOwnerChanged,
+};
+pub use history::{EventMetadata, RegistryEvent};
+pub use indexer::{AddressLink, AddressRole, RegistryIndex};
+pub use jsonld::{canonicalize, compact, expand, load_context, undefined_terms};
+pub use jwt::{verify_jwt, JwtValidation, JwtVerification};
+pub use ld_proof::{verify_ld_proof, LdProofVerification};
+pub use profile::{
+    AccountEncoding, InvalidKeyPolicy, InvalidServicePolicy, KeyEncoding, OutputProfile,
+};
+pub use proof::{
+    encode_receipt, header_hash, ordered_trie_root, verify_trie_proof, MAX_HEADER_WALK,
+};
+#[cfg(feature = "python")]
+pub use python::PyResolver;
+pub use representation::{to_cbor, DID_CBOR, DID_JSON, DID_LD_JSON};
+pub use resolution::{DidDocumentMetadata, DidResolutionMetadata, DidResolutionResult};
+pub use resolver::{EthrResolver, EthrResolverBuilder, NetworkConfig};
+pub use router::{DidMethodRouter, DidResolver, ResolutionFuture};
+pub use signature::ProofPurpose;
+pub use transport::{RateLimiter, RpcPolicy, RpcTransport};
+
+static DID_ETHR: OnceLock<Regex> = OnceLock::new();
 
 pub async fn resolve(did: &str, provider: &str, accept: &str) -> Result<DidDocument, Error> {
-    let context: Vec<String> = match accept {
-        "application/did+json" => Vec::new(),
-        "application/did+ld+json" => Vec::from([
-            String::from("https://www.w3.org/ns/did/v1"),
-            String::from("https://w3id.org/security/suites/secp256k1recovery-2020/v2"),
-            String::from("https://w3id.org/security/v3-unstable"),
-        ]),
-        _ => {
+    match resolve_with_metadata(did, provider, accept).await {
+        Ok(val) => Ok(val.did_document),
+        Err(error) => Err(error),
+    }
+}
+
+pub async fn resolve_representation(
+    did: &str,
+    provider: &str,
+    accept: &str,
+) -> Result<Vec<u8>, Error> {
+    match resolve_with_metadata(did, provider, accept).await {
+        Ok(val) => val.representation(),
+        Err(error) => Err(error),
+    }
+}
+
+pub async fn resolve_with_profile(
+    did: &str,
+    provider: &str,
+    accept: &str,
+    profile: &OutputProfile,
+) -> Result<Value, Error> {
+    match resolve_document(did, provider, accept, profile).await {
+        Ok(val) => val.document(),
+        Err(error) => Err(error),
+    }
+}
+
+fn to_profile_value(
+    did_document: &DidDocument,
+    accept: &str,
+    profile: &OutputProfile,
+) -> Result<Value, Error> {
+    let document = apply_output_profile(did_document, profile)?;
+
+    if accept == DID_LD_JSON && profile.strict_terms {
+        let terms = undefined_terms(&document)?;
+
+        if !terms.is_empty() {
             return Err(Error::new(
                 format!(
-                    "The DID resolver does not support the requested 'accept' format: {}",
-                    accept
+                    "The DID document uses undefined JSON-LD terms: {}",
+                    terms.join(", ")
                 )
                 .as_str(),
-            ))
+            ));
         }
+    }
+
+    Ok(document)
+}
+
+pub async fn resolve_with_metadata(
+    did: &str,
+    provider: &str,
+    accept: &str,
+) -> Result<DidResolutionResult, Error> {
+    resolve_document(did, provider, accept, &OutputProfile::default()).await
+}
+
+async fn resolve_document(
+    did: &str,
+    provider: &str,
+    accept: &str,
+    profile: &OutputProfile,
+) -> Result<DidResolutionResult, Error> {
+    let mut did_doc = match new_document(did, accept) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
     };
 
-    let regex = match Regex::new("^(.*)?(0x[0-9a-fA-F]{40}|0x[0-9a-fA-F]{66})$") {
+    let contract_address = match get_identity_address(did) {
         Ok(val) => val,
-        Err(error) => return Err(Error::new(error.to_string().as_str())),
+        Err(error) => return Err(error),
     };
 
-    if !regex.is_match(did) {
-        return Err(Error::new(
-            format!("Not a valid did:ethr: {}", did).as_str(),
-        ));
+    match build_did_doc_from_logs(provider, contract_address.as_str(), &mut did_doc, profile).await
+    {
+        Ok(val) => to_resolution_result(accept, val, profile),
+        Err(error) => Err(error),
     }
+}
 
-    let did_components = did.split(":").collect::<Vec<&str>>();
-    /*
-    let network = match did_components.len() >= 4 {
-        true => did_components[2..did_components.len() - 1].join(":"),
-        false => String::from(""),
-    };
-    */
+pub fn resolve_from_logs(
+    did: &str,
+    accept: &str,
+    chain_id: u64,
+    now: Option<u64>,
+    logs: Vec<Log>,
+    profile: &OutputProfile,
+) -> Result<DidResolutionResult, Error> {
+    let mut did_doc = new_document(did, accept)?;
+    let contract_address = get_identity_address(did)?;
 
-    let contract_address = strip0x(String::from(*did_components.last().unwrap()));
+    let created = build_did_doc_from_fetched_logs(
+        contract_address.as_str(),
+        &mut did_doc,
+        chain_id,
+        now,
+        logs,
+        profile,
+    )?;
 
-    let mut did_doc = DidDocument {
-        context,
+    to_resolution_result(accept, created, profile)
+}
+
+fn new_document(did: &str, accept: &str) -> Result<DidDocument, Error> {
+    if !is_supported(accept) {
+        return Err(Error::new(
+            format!(
+                "The DID resolver does not support the requested 'accept' format: {}",
+                accept
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(DidDocument {
+        context: Vec::new(),
         id: String::from(did),
         verification_method: None,
         authentication: None,
@@ -59,13 +207,116 @@ pub async fn resolve(did: &str, provider: &str, accept: &str) -> Result<DidDocum
         capability_invocation: None,
         key_agreement: None,
         services: None,
+    })
+}
+
+fn to_resolution_result(
+    accept: &str,
+    created: (DidDocument, bool, Option<u64>, Vec<String>),
+    profile: &OutputProfile,
+) -> Result<DidResolutionResult, Error> {
+    let (mut created_did_doc, deactivated, version_id, invalid_keys) = created;
+
+    if accept == DID_LD_JSON {
+        created_did_doc.context = document_context(&created_did_doc);
+    }
+
+    let result = DidResolutionResult {
+        did_resolution_metadata: DidResolutionMetadata {
+            content_type: String::from(accept),
+        },
+        did_document: created_did_doc,
+        did_document_metadata: DidDocumentMetadata {
+            deactivated: match deactivated {
+                true => Some(true),
+                false => None,
+            },
+            version_id: version_id.map(|val| val.to_string()),
+            invalid_keys: match invalid_keys.is_empty() {
+                true => None,
+                false => Some(invalid_keys),
+            },
+            partial: None,
+        },
+        profile: profile.clone(),
+    };
+
+    if profile.strict_terms {
+        result.document()?;
+    }
+
+    Ok(result)
+}
+
+pub async fn history(did: &str, provider: &str) -> Result<Vec<RegistryEvent>, Error> {
+    let address = match get_identity_address(did) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
     };
 
-    let (created_did_doc, _deactivated, _version_id) =
-        match build_did_doc_from_logs(provider, contract_address.as_str(), &mut did_doc).await {
+    get_history(provider, address.as_str()).await
+}
+
+#[cfg(feature = "native")]
+pub fn watch(
+    did: &str,
+    provider: &str,
+) -> Result<impl Stream<Item = Result<RegistryEvent, Error>>, Error> {
+    let address = match get_identity_address(did) {
+        Ok(val) => match val.parse::<Address>() {
             Ok(val) => val,
-            Err(error) => return Err(error),
-        };
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        },
+        Err(error) => return Err(error),
+    };
+
+    let registry = parse_address(DEFAULT_REGISTRY)?;
+
+    watch_registry(provider, registry, Some(address))
+}
+
+#[cfg(feature = "native")]
+pub fn watch_all(
+    registry: &str,
+    provider: &str,
+) -> Result<impl Stream<Item = Result<RegistryEvent, Error>>, Error> {
+    let address = match strip0x(String::from(registry)).parse::<Address>() {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
 
-    Ok(created_did_doc)
+    watch_registry(provider, address, None)
+}
+
+fn get_identity_address(did: &str) -> Result<String, Error> {
+    let regex = DID_ETHR
+        .get_or_init(|| Regex::new("^(.*)?(0x[0-9a-fA-F]{40}|0x[0-9a-fA-F]{66})$").unwrap());
+
+    if !regex.is_match(did) {
+        return Err(Error::new(
+            format!("Not a valid did:ethr: {}", did).as_str(),
+        ));
+    }
+
+    let did_components = did.split(":").collect::<Vec<&str>>();
+    let identifier = *did_components.last().unwrap();
+
+    match identifier.len() {
+        68 => match public_key_hex_to_address(identifier) {
+            Some(val) => Ok(strip0x(val)),
+            None => Err(Error::new(
+                format!("Not a valid secp256k1 public key: {}", identifier).as_str(),
+            )),
+        },
+        _ => Ok(strip0x(String::from(identifier))),
+    }
+}
+
+fn get_network(did: &str) -> String {
+    let did_components = did.split(":").collect::<Vec<&str>>();
+
+    match did_components.len() >= 4 {
+        true => did_components[2..did_components.len() - 1].join(":"),
+        false => String::from("mainnet"),
+    }
 }
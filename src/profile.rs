@@ -0,0 +1,252 @@
+use base64::Engine;
+use fi_common::{did::DidDocument, error::Error};
+use serde_json::{Map, Value};
+
+use crate::caip::{AccountId, EIP155};
+use crate::events::attribute_handler::AttributeHandlers;
+use crate::representation::{document_value, value_context};
+use crate::util::{compress_public_key, decompress_public_key};
+use crate::verification::{
+    ECDSA_SECP256K1_VERIFICATION_KEY2019, ED25519_VERIFICATION_KEY2018,
+    X25519_KEY_AGREEMENT_KEY2019,
+};
+
+pub const JSON_WEB_KEY2020: &str = "JsonWebKey2020";
+pub const MULTIKEY: &str = "Multikey";
+
+const SECP256K1_PUB_CODEC: u64 = 0xe7;
+const ED25519_PUB_CODEC: u64 = 0xed;
+const X25519_PUB_CODEC: u64 = 0xec;
+
+const LEGACY_KEY_FIELDS: [&str; 3] = ["publicKeyHex", "publicKeyBase64", "publicKeyBase58"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyEncoding {
+    #[default]
+    Legacy,
+    Jwk,
+    Multibase,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AccountEncoding {
+    #[default]
+    BlockchainAccountId,
+    EthereumAddress,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidKeyPolicy {
+    #[default]
+    Flag,
+    Drop,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidServicePolicy {
+    #[default]
+    Drop,
+    Reject,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputProfile {
+    pub key_encoding: KeyEncoding,
+    pub account_encoding: AccountEncoding,
+    pub invalid_keys: InvalidKeyPolicy,
+    pub invalid_services: InvalidServicePolicy,
+    pub strict_terms: bool,
+    pub attribute_handlers: AttributeHandlers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    Secp256k1,
+    Ed25519,
+    X25519,
+}
+
+pub fn apply_output_profile(
+    document: &DidDocument,
+    profile: &OutputProfile,
+) -> Result<Value, Error> {
+    let mut value = document_value(document)?;
+
+    if profile.key_encoding == KeyEncoding::Legacy
+        && profile.account_encoding == AccountEncoding::BlockchainAccountId
+    {
+        return Ok(value);
+    }
+
+    for relationship in ["verificationMethod", "keyAgreement"] {
+        if let Some(Value::Array(methods)) = value.get_mut(relationship) {
+            for method in methods.iter_mut() {
+                if let Value::Object(method) = method {
+                    encode_method(method, profile.key_encoding);
+                    encode_account(method, profile.account_encoding);
+                }
+            }
+        }
+    }
+
+    if value["@context"]
+        .as_array()
+        .is_some_and(|val| !val.is_empty())
+    {
+        value["@context"] = Value::from(value_context(&value));
+    }
+
+    Ok(value)
+}
+
+fn encode_method(method: &mut Map<String, Value>, encoding: KeyEncoding) {
+    let curve = match method.get("type").and_then(|val| val.as_str()) {
+        Some(ECDSA_SECP256K1_VERIFICATION_KEY2019) => Curve::Secp256k1,
+        Some(ED25519_VERIFICATION_KEY2018) => Curve::Ed25519,
+        Some(X25519_KEY_AGREEMENT_KEY2019) => Curve::X25519,
+        _ => return,
+    };
+
+    let bytes = match raw_key_bytes(method) {
+        Some(val) => val,
+        None => return,
+    };
+
+    let (_type, field, encoded) = match encoding {
+        KeyEncoding::Jwk => match to_jwk(curve, &bytes) {
+            Some(val) => (JSON_WEB_KEY2020, "publicKeyJwk", val),
+            None => return,
+        },
+        KeyEncoding::Multibase => match to_multibase(curve, &bytes) {
+            Some(val) => (MULTIKEY, "publicKeyMultibase", Value::String(val)),
+            None => return,
+        },
+        KeyEncoding::Legacy => return,
+    };
+
+    for legacy in LEGACY_KEY_FIELDS {
+        method.remove(legacy);
+    }
+
+    method.insert(String::from("type"), Value::String(String::from(_type)));
+    method.insert(String::from(field), encoded);
+}
+
+fn encode_account(method: &mut Map<String, Value>, encoding: AccountEncoding) {
+    if encoding == AccountEncoding::BlockchainAccountId {
+        return;
+    }
+
+    let account_id = match method
+        .get("blockchainAccountId")
+        .and_then(|val| val.as_str())
+        .and_then(|val| AccountId::parse(val).ok())
+    {
+        Some(val) => val,
+        None => return,
+    };
+
+    if account_id.chain_id.namespace != EIP155 {
+        return;
+    }
+
+    method.remove("blockchainAccountId");
+    method.insert(
+        String::from("ethereumAddress"),
+        Value::String(account_id.address),
+    );
+}
+
+fn raw_key_bytes(method: &Map<String, Value>) -> Option<Vec<u8>> {
+    if let Some(val) = method.get("publicKeyHex").and_then(|val| val.as_str()) {
+        return hex::decode(val.trim_start_matches("0x")).ok();
+    }
+
+    if let Some(val) = method.get("publicKeyBase64").and_then(|val| val.as_str()) {
+        return base64::engine::general_purpose::STANDARD.decode(val).ok();
+    }
+
+    if let Some(val) = method.get("publicKeyBase58").and_then(|val| val.as_str()) {
+        return bs58::decode(val).into_vec().ok();
+    }
+
+    None
+}
+
+fn to_jwk(curve: Curve, bytes: &[u8]) -> Option<Value> {
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let mut jwk = Map::new();
+
+    match curve {
+        Curve::Secp256k1 => {
+            let uncompressed = decompress_public_key(bytes)?;
+
+            jwk.insert(String::from("kty"), Value::String(String::from("EC")));
+            jwk.insert(
+                String::from("crv"),
+                Value::String(String::from("secp256k1")),
+            );
+            jwk.insert(
+                String::from("x"),
+                Value::String(engine.encode(&uncompressed[1..33])),
+            );
+            jwk.insert(
+                String::from("y"),
+                Value::String(engine.encode(&uncompressed[33..65])),
+            );
+        }
+        Curve::Ed25519 | Curve::X25519 => {
+            if bytes.len() != 32 {
+                return None;
+            }
+
+            jwk.insert(String::from("kty"), Value::String(String::from("OKP")));
+            jwk.insert(
+                String::from("crv"),
+                Value::String(String::from(match curve {
+                    Curve::Ed25519 => "Ed25519",
+                    _ => "X25519",
+                })),
+            );
+            jwk.insert(String::from("x"), Value::String(engine.encode(bytes)));
+        }
+    };
+
+    Some(Value::Object(jwk))
+}
+
+fn to_multibase(curve: Curve, bytes: &[u8]) -> Option<String> {
+    let (codec, key) = match curve {
+        Curve::Secp256k1 => (SECP256K1_PUB_CODEC, compress_public_key(bytes)?.to_vec()),
+        Curve::Ed25519 | Curve::X25519 if bytes.len() == 32 => (
+            match curve {
+                Curve::Ed25519 => ED25519_PUB_CODEC,
+                _ => X25519_PUB_CODEC,
+            },
+            bytes.to_vec(),
+        ),
+        _ => return None,
+    };
+
+    let mut prefixed = encode_varint(codec);
+    prefixed.extend(key);
+
+    Some(format!("z{}", bs58::encode(prefixed).into_string()))
+}
+
+fn encode_varint(value: u64) -> Vec<u8> {
+    let mut value = value;
+    let mut result = Vec::new();
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            result.push(byte);
+            return result;
+        }
+
+        result.push(byte | 0x80);
+    }
+}
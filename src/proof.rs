@@ -0,0 +1,534 @@
+use ethers::abi::{encode, Token};
+use ethers::providers::{JsonRpcClient, Middleware, Provider};
+use ethers::types::{
+    Block, BlockNumber, Bytes, EIP1186ProofResponse, Log, TransactionReceipt, H160, H256, U256,
+};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::{self, Rlp, RlpStream};
+use fi_common::error::Error;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::ethr::verify_change_chain;
+use crate::events::attribute_changed::DIDAttributeChanged;
+use crate::events::delegate_changed::DIDDelegateChanged;
+use crate::events::owner_changed::DIDOwnerChanged;
+use crate::events::DiDEthrChangeEvent;
+use crate::transport::RpcTransport;
+
+const CHANGED_SLOT: u64 = 2;
+pub const MAX_HEADER_WALK: u64 = 128;
+
+enum NodeRef {
+    Hash(H256),
+    Inline(Vec<u8>),
+}
+
+pub async fn get_verified_logs<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    registry: H160,
+    identity: H160,
+    block_hash: H256,
+    checkpoints: &[H256],
+) -> Result<(Vec<Log>, u64), Error> {
+    let mut trusted = BTreeMap::<u64, Block<H256>>::new();
+
+    let anchor = match get_verified_block(client, block_hash).await {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    for checkpoint in checkpoints {
+        match get_verified_block(client, *checkpoint).await {
+            Ok(val) => trusted.insert(block_number(&val), val),
+            Err(error) => return Err(error),
+        };
+    }
+
+    let slot = changed_slot(identity);
+
+    let proof = match client
+        .get_proof(registry, vec![slot], Some(block_hash.into()))
+        .await
+    {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    let mut previous_change = match verify_changed(&anchor, registry, slot, &proof) {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    let timestamp = anchor.timestamp.as_u64();
+    trusted.insert(block_number(&anchor), anchor);
+    let mut event_log = Vec::<Log>::new();
+
+    while previous_change != 0 {
+        let block = match get_linked_block(client, &mut trusted, previous_change).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let receipts = match client
+            .get_block_receipts(BlockNumber::Number(previous_change.into()))
+            .await
+        {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        let mut logs = match verify_receipts(&block, receipts, registry, identity) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        previous_change = match verify_change_chain(identity, previous_change, &mut logs) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        logs.append(&mut event_log);
+        event_log = logs;
+    }
+
+    Ok((event_log, timestamp))
+}
+
+pub fn header_hash(block: &Block<H256>) -> H256 {
+    let mut stream = RlpStream::new();
+    stream.begin_unbounded_list();
+
+    stream.append(&block.parent_hash);
+    stream.append(&block.uncles_hash);
+    stream.append(&block.author.unwrap_or_default());
+    stream.append(&block.state_root);
+    stream.append(&block.transactions_root);
+    stream.append(&block.receipts_root);
+    stream.append(&block.logs_bloom.unwrap_or_default());
+    stream.append(&block.difficulty);
+    stream.append(&block.number.unwrap_or_default());
+    stream.append(&block.gas_limit);
+    stream.append(&block.gas_used);
+    stream.append(&block.timestamp);
+    stream.append(&block.extra_data.to_vec());
+    stream.append(&block.mix_hash.unwrap_or_default());
+    stream.append(&block.nonce.unwrap_or_default());
+
+    if let Some(val) = block.base_fee_per_gas {
+        stream.append(&val);
+    }
+    if let Some(val) = block.withdrawals_root {
+        stream.append(&val);
+    }
+    if let Some(val) = block.blob_gas_used {
+        stream.append(&val);
+    }
+    if let Some(val) = block.excess_blob_gas {
+        stream.append(&val);
+    }
+    if let Some(val) = block.parent_beacon_block_root {
+        stream.append(&val);
+    }
+    if let Some(Ok(val)) = block.other.get_deserialized::<H256>("requestsHash") {
+        stream.append(&val);
+    }
+
+    stream.finalize_unbounded_list();
+    H256(keccak256(stream.out()))
+}
+
+pub fn ordered_trie_root(values: &[Vec<u8>]) -> H256 {
+    let entries = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| (to_nibbles(&rlp::encode(&(index as u64))), value.as_slice()))
+        .collect::<Vec<(Vec<u8>, &[u8])>>();
+
+    match entries.is_empty() {
+        true => empty_root(),
+        false => H256(keccak256(encode_node(&entries, 0))),
+    }
+}
+
+pub fn verify_trie_proof(
+    root: H256,
+    key: &[u8],
+    proof: &[Bytes],
+) -> Result<Option<Vec<u8>>, Error> {
+    if root == empty_root() {
+        return Ok(None);
+    }
+
+    let path = to_nibbles(&keccak256(key));
+    let mut nodes = proof.iter();
+    let mut expected = NodeRef::Hash(root);
+    let mut depth = 0;
+
+    loop {
+        let node = match expected {
+            NodeRef::Hash(hash) => match nodes.next() {
+                Some(val) if H256(keccak256(val)) == hash => val.to_vec(),
+                Some(_val) => {
+                    return Err(Error::new(
+                        "The proof does not match the expected trie root",
+                    ))
+                }
+                None => return Err(Error::new("The proof ended before the key was resolved")),
+            },
+            NodeRef::Inline(val) => val,
+        };
+
+        let node = Rlp::new(&node);
+
+        match node.item_count() {
+            Ok(17) => {
+                if depth == path.len() {
+                    return optional_value(&node.at(16).map_err(malformed)?);
+                }
+
+                let child = node.at(usize::from(path[depth])).map_err(malformed)?;
+                depth += 1;
+
+                expected = match node_ref(&child)? {
+                    Some(val) => val,
+                    None => return Ok(None),
+                };
+            }
+            Ok(2) => {
+                let (partial, leaf) =
+                    decode_path(node.at(0).and_then(|val| val.data()).map_err(malformed)?);
+
+                if leaf {
+                    return match path[depth..] == partial[..] {
+                        true => optional_value(&node.at(1).map_err(malformed)?),
+                        false => Ok(None),
+                    };
+                }
+
+                if !path[depth..].starts_with(&partial) {
+                    return Ok(None);
+                }
+                depth += partial.len();
+
+                expected = match node_ref(&node.at(1).map_err(malformed)?)? {
+                    Some(val) => val,
+                    None => return Err(malformed(rlp::DecoderError::Custom("empty child"))),
+                };
+            }
+            _ => return Err(malformed(rlp::DecoderError::RlpExpectedToBeList)),
+        }
+    }
+}
+
+async fn get_verified_block<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    hash: H256,
+) -> Result<Block<H256>, Error> {
+    let block = match client.get_block(hash).await {
+        Ok(Some(val)) => val,
+        Ok(None) => {
+            return Err(Error::new(
+                format!("Block {:#x} was not found", hash).as_str(),
+            ))
+        }
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    if block.number.is_none() || header_hash(&block) != hash {
+        return Err(Error::new(
+            format!(
+                "The provider returned a block header that does not hash to {:#x}",
+                hash
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(block)
+}
+
+async fn get_linked_block<C: JsonRpcClient + 'static>(
+    client: &Arc<Provider<RpcTransport<C>>>,
+    trusted: &mut BTreeMap<u64, Block<H256>>,
+    number: u64,
+) -> Result<Block<H256>, Error> {
+    let mut block = match trusted.range(number..).next() {
+        Some((_number, val)) => val.clone(),
+        None => {
+            return Err(Error::new(
+                format!("Block {} is newer than the trusted block", number).as_str(),
+            ))
+        }
+    };
+
+    let mut steps = 0;
+
+    while block_number(&block) > number {
+        if steps == MAX_HEADER_WALK {
+            return Err(Error::new(
+                format!(
+                    "Block {} is more than {} headers below the nearest trusted block; pass a checkpoint closer to it",
+                    number, MAX_HEADER_WALK
+                )
+                .as_str(),
+            ));
+        }
+
+        block = match get_verified_block(client, block.parent_hash).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+        trusted.insert(block_number(&block), block.clone());
+        steps += 1;
+    }
+
+    Ok(block)
+}
+
+fn verify_changed(
+    block: &Block<H256>,
+    registry: H160,
+    slot: H256,
+    proof: &EIP1186ProofResponse,
+) -> Result<u64, Error> {
+    let account =
+        match verify_trie_proof(block.state_root, registry.as_bytes(), &proof.account_proof) {
+            Ok(Some(val)) => val,
+            Ok(None) => {
+                return Err(Error::new(
+                    format!(
+                        "The registry {:#x} does not exist at block {}",
+                        registry,
+                        block_number(block)
+                    )
+                    .as_str(),
+                ))
+            }
+            Err(error) => return Err(error),
+        };
+
+    let storage_root = Rlp::new(&account).val_at::<H256>(2).map_err(malformed)?;
+
+    let storage_proof = match proof
+        .storage_proof
+        .iter()
+        .find(|val| val.key == U256::from(slot.as_bytes()))
+    {
+        Some(val) => val,
+        None => return Err(Error::new("The provider did not return a storage proof")),
+    };
+
+    let changed = match verify_trie_proof(storage_root, slot.as_bytes(), &storage_proof.proof)? {
+        Some(val) => Rlp::new(&val).as_val::<U256>().map_err(malformed)?,
+        None => U256::zero(),
+    };
+
+    match changed > U256::from(block_number(block)) {
+        true => Err(Error::new(
+            "The proven registry change is newer than its block",
+        )),
+        false => Ok(changed.as_u64()),
+    }
+}
+
+fn verify_receipts(
+    block: &Block<H256>,
+    mut receipts: Vec<TransactionReceipt>,
+    registry: H160,
+    identity: H160,
+) -> Result<Vec<Log>, Error> {
+    receipts.sort_by_key(|receipt| receipt.transaction_index);
+
+    let encoded = receipts
+        .iter()
+        .map(encode_receipt)
+        .collect::<Vec<Vec<u8>>>();
+
+    if ordered_trie_root(&encoded) != block.receipts_root {
+        return Err(Error::new(
+            format!(
+                "The receipts returned for block {} do not match its receipts root",
+                block_number(block)
+            )
+            .as_str(),
+        ));
+    }
+
+    let mut logs = Vec::<Log>::new();
+    let mut log_index = 0u64;
+
+    for receipt in receipts {
+        for log in receipt.logs {
+            let is_identity_event = log.address == registry
+                && log.topics.get(1) == Some(&H256::from(identity))
+                && (DIDOwnerChanged::is_event_of(&log.topics)
+                    || DIDDelegateChanged::is_event_of(&log.topics)
+                    || DIDAttributeChanged::is_event_of(&log.topics));
+
+            if is_identity_event {
+                logs.push(Log {
+                    block_hash: block.hash,
+                    block_number: block.number,
+                    transaction_hash: Some(receipt.transaction_hash),
+                    transaction_index: Some(receipt.transaction_index),
+                    log_index: Some(log_index.into()),
+                    ..log
+                });
+            }
+            log_index += 1;
+        }
+    }
+
+    Ok(logs)
+}
+
+pub fn encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+
+    match (receipt.status, receipt.root) {
+        (Some(status), _) => stream.append(&status),
+        (None, Some(root)) => stream.append(&root),
+        (None, None) => stream.append_empty_data(),
+    };
+    stream.append(&receipt.cumulative_gas_used);
+    stream.append(&receipt.logs_bloom);
+    stream.append_list(&receipt.logs);
+
+    let mut encoded = stream.out().to_vec();
+
+    if let Some(transaction_type) = receipt.transaction_type.filter(|val| !val.is_zero()) {
+        encoded.insert(0, transaction_type.as_u64() as u8);
+    }
+
+    encoded
+}
+
+fn encode_node(entries: &[(Vec<u8>, &[u8])], depth: usize) -> Vec<u8> {
+    if let [(key, value)] = entries {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encode_path(&key[depth..], true));
+        stream.append(&value.to_vec());
+        return stream.out().to_vec();
+    }
+
+    let prefix = entries
+        .iter()
+        .map(|(key, _value)| {
+            key[depth..]
+                .iter()
+                .zip(&entries[0].0[depth..])
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .min()
+        .unwrap_or(0);
+
+    if prefix > 0 {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encode_path(&entries[0].0[depth..depth + prefix], false));
+        append_child(&mut stream, encode_node(entries, depth + prefix));
+        return stream.out().to_vec();
+    }
+
+    let mut stream = RlpStream::new_list(17);
+
+    for nibble in 0..16u8 {
+        let children = entries
+            .iter()
+            .filter(|(key, _value)| key.len() > depth && key[depth] == nibble)
+            .cloned()
+            .collect::<Vec<(Vec<u8>, &[u8])>>();
+
+        match children.is_empty() {
+            true => stream.append_empty_data(),
+            false => append_child(&mut stream, encode_node(&children, depth + 1)),
+        };
+    }
+
+    match entries.iter().find(|(key, _value)| key.len() == depth) {
+        Some((_key, value)) => stream.append(&value.to_vec()),
+        None => stream.append_empty_data(),
+    };
+
+    stream.out().to_vec()
+}
+
+fn append_child(stream: &mut RlpStream, node: Vec<u8>) -> &mut RlpStream {
+    match node.len() < 32 {
+        true => stream.append_raw(&node, 1),
+        false => stream.append(&H256(keccak256(node))),
+    }
+}
+
+fn node_ref(item: &Rlp) -> Result<Option<NodeRef>, Error> {
+    if item.is_list() {
+        return Ok(Some(NodeRef::Inline(item.as_raw().to_vec())));
+    }
+
+    match item.data().map_err(malformed)? {
+        [] => Ok(None),
+        val if val.len() == 32 => Ok(Some(NodeRef::Hash(H256::from_slice(val)))),
+        _ => Err(malformed(rlp::DecoderError::RlpInvalidLength)),
+    }
+}
+
+fn optional_value(item: &Rlp) -> Result<Option<Vec<u8>>, Error> {
+    match item.data().map_err(malformed)? {
+        [] => Ok(None),
+        val => Ok(Some(val.to_vec())),
+    }
+}
+
+fn encode_path(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = match leaf {
+        true => 2,
+        false => 0,
+    };
+
+    let (mut encoded, rest) = match nibbles.len() % 2 {
+        1 => (vec![((flag + 1) << 4) | nibbles[0]], &nibbles[1..]),
+        _ => (vec![flag << 4], nibbles),
+    };
+
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+fn decode_path(encoded: &[u8]) -> (Vec<u8>, bool) {
+    let nibbles = to_nibbles(encoded);
+    let flag = nibbles.first().copied().unwrap_or(0);
+
+    let path = match flag % 2 {
+        1 => nibbles[1..].to_vec(),
+        _ => nibbles.iter().skip(2).copied().collect(),
+    };
+
+    (path, flag >= 2)
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn changed_slot(identity: H160) -> H256 {
+    H256(keccak256(encode(&[
+        Token::Address(identity),
+        Token::Uint(CHANGED_SLOT.into()),
+    ])))
+}
+
+fn empty_root() -> H256 {
+    H256(keccak256(rlp::NULL_RLP))
+}
+
+fn block_number(block: &Block<H256>) -> u64 {
+    block.number.map_or(0, |val| val.as_u64())
+}
+
+fn malformed(error: rlp::DecoderError) -> Error {
+    Error::new(format!("The proof contains a malformed trie node: {}", error).as_str())
+}
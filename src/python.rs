@@ -0,0 +1,80 @@
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{history, resolve_with_metadata};
+
+#[pyclass(name = "Resolver", module = "fi_ethr_resolver")]
+pub struct PyResolver {
+    provider: String,
+    runtime: Runtime,
+}
+
+#[pymethods]
+impl PyResolver {
+    #[new]
+    fn new(provider: String) -> PyResult<Self> {
+        let runtime = match Builder::new_multi_thread().enable_all().build() {
+            Ok(val) => val,
+            Err(error) => return Err(PyRuntimeError::new_err(error.to_string())),
+        };
+
+        Ok(PyResolver { provider, runtime })
+    }
+
+    #[pyo3(signature = (did, accept = "application/did+json"))]
+    fn resolve(&self, py: Python<'_>, did: &str, accept: &str) -> PyResult<PyObject> {
+        let result = py.allow_threads(|| {
+            self.runtime
+                .block_on(resolve_with_metadata(did, self.provider.as_str(), accept))
+                .and_then(|val| val.document())
+        });
+
+        match result {
+            Ok(val) => to_python(py, &val),
+            Err(error) => Err(PyValueError::new_err(error.to_string())),
+        }
+    }
+
+    #[pyo3(signature = (did, accept = "application/did+json"))]
+    fn resolve_with_metadata(&self, py: Python<'_>, did: &str, accept: &str) -> PyResult<PyObject> {
+        let result = py.allow_threads(|| {
+            self.runtime
+                .block_on(resolve_with_metadata(did, self.provider.as_str(), accept))
+        });
+
+        match result {
+            Ok(val) => to_python(py, &val),
+            Err(error) => Err(PyValueError::new_err(error.to_string())),
+        }
+    }
+
+    fn history(&self, py: Python<'_>, did: &str) -> PyResult<PyObject> {
+        let result =
+            py.allow_threads(|| self.runtime.block_on(history(did, self.provider.as_str())));
+
+        match result {
+            Ok(val) => to_python(py, &val),
+            Err(error) => Err(PyValueError::new_err(error.to_string())),
+        }
+    }
+}
+
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = match serde_json::to_string(value) {
+        Ok(val) => val,
+        Err(error) => return Err(PyRuntimeError::new_err(error.to_string())),
+    };
+
+    let loaded = py.import_bound("json")?.call_method1("loads", (json,))?;
+    Ok(loaded.unbind())
+}
+
+#[pymodule]
+fn fi_ethr_resolver(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyResolver>()?;
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use fi_common::{did::DidDocument, error::Error};
+use serde_json::{Map, Value};
+
+use crate::jsonld::undefined_terms;
+use crate::profile::{JSON_WEB_KEY2020, MULTIKEY};
+use crate::verification::{
+    ECDSA_SECP256K1_RECOVERY_METHOD2020, ECDSA_SECP256K1_VERIFICATION_KEY2019,
+    ED25519_VERIFICATION_KEY2018, X25519_KEY_AGREEMENT_KEY2019,
+};
+
+pub const DID_JSON: &str = "application/did+json";
+pub const DID_LD_JSON: &str = "application/did+ld+json";
+pub const DID_CBOR: &str = "application/did+cbor";
+
+pub const DID_V1_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+pub const SECP256K1_RECOVERY_2020_V2_CONTEXT: &str =
+    "https://w3id.org/security/suites/secp256k1recovery-2020/v2";
+pub const SECP256K1_2019_V1_CONTEXT: &str = "https://w3id.org/security/suites/secp256k1-2019/v1";
+pub const ED25519_2018_V1_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2018/v1";
+pub const X25519_2019_V1_CONTEXT: &str = "https://w3id.org/security/suites/x25519-2019/v1";
+pub const JWS_2020_V1_CONTEXT: &str = "https://w3id.org/security/suites/jws-2020/v1";
+pub const MULTIKEY_V1_CONTEXT: &str = "https://w3id.org/security/multikey/v1";
+pub const SECURITY_V3_UNSTABLE_CONTEXT: &str = "https://w3id.org/security/v3-unstable";
+
+pub fn is_supported(accept: &str) -> bool {
+    matches!(accept, DID_JSON | DID_LD_JSON | DID_CBOR)
+}
+
+pub fn method_context(_type: &str) -> &'static str {
+    match _type {
+        ECDSA_SECP256K1_RECOVERY_METHOD2020 => SECP256K1_RECOVERY_2020_V2_CONTEXT,
+        ECDSA_SECP256K1_VERIFICATION_KEY2019 => SECP256K1_2019_V1_CONTEXT,
+        ED25519_VERIFICATION_KEY2018 => ED25519_2018_V1_CONTEXT,
+        X25519_KEY_AGREEMENT_KEY2019 => X25519_2019_V1_CONTEXT,
+        JSON_WEB_KEY2020 => JWS_2020_V1_CONTEXT,
+        MULTIKEY => MULTIKEY_V1_CONTEXT,
+        _ => SECURITY_V3_UNSTABLE_CONTEXT,
+    }
+}
+
+pub fn assemble_context(methods: &[Value]) -> Vec<String> {
+    let mut context = vec![String::from(DID_V1_CONTEXT)];
+
+    for method in methods {
+        let url = String::from(method_context(method["type"].as_str().unwrap_or_default()));
+        if !context.contains(&url) {
+            context.push(url);
+        }
+    }
+
+    let fallback = String::from(SECURITY_V3_UNSTABLE_CONTEXT);
+    if !context.contains(&fallback) {
+        let mut document = Map::new();
+        document.insert(String::from("@context"), Value::from(context.clone()));
+        document.insert(
+            String::from("verificationMethod"),
+            Value::Array(methods.to_vec()),
+        );
+
+        if undefined_terms(&Value::Object(document)).is_ok_and(|val| !val.is_empty()) {
+            context.push(fallback);
+        }
+    }
+
+    context
+}
+
+pub fn document_context(document: &DidDocument) -> Vec<String> {
+    match document_value(document) {
+        Ok(val) => value_context(&val),
+        Err(_error) => vec![String::from(DID_V1_CONTEXT)],
+    }
+}
+
+/// Serializes a document into its DID Core shape. `fi_common` writes unset
+/// members as `null`, empty relationships as `[]`, services under `services`
+/// and endpoints as `service_endpoint`, none of which DID Core allows.
+pub fn document_value(document: &DidDocument) -> Result<Value, Error> {
+    let mut value = match serde_json::to_value(document) {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    for relationship in ["verificationMethod", "keyAgreement"] {
+        if let Some(Value::Array(methods)) = value.get_mut(relationship) {
+            for method in methods.iter_mut() {
+                if let Value::Object(method) = method {
+                    method.retain(|_key, val| !val.is_null());
+                }
+            }
+        }
+    }
+
+    if let Some(members) = value.as_object_mut() {
+        if let Some(services) = members.remove("services") {
+            members.insert(String::from("service"), services);
+        }
+
+        members.retain(|_key, val| match val {
+            Value::Null => false,
+            Value::Array(items) => !items.is_empty(),
+            _ => true,
+        });
+    }
+
+    if let Some(Value::Array(services)) = value.get_mut("service") {
+        for service in services.iter_mut() {
+            if let Value::Object(service) = service {
+                if let Some(endpoint) = service.remove("service_endpoint") {
+                    service.insert(String::from("serviceEndpoint"), endpoint);
+                }
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+pub fn value_context(document: &Value) -> Vec<String> {
+    let methods = ["verificationMethod", "keyAgreement"]
+        .iter()
+        .filter_map(|relationship| document[*relationship].as_array())
+        .flatten()
+        .filter(|method| method.is_object())
+        .cloned()
+        .collect::<Vec<Value>>();
+
+    assemble_context(&methods)
+}
+
+pub fn represent(document: &Value, accept: &str) -> Result<Vec<u8>, Error> {
+    match accept {
+        DID_CBOR => Ok(to_cbor(document)),
+        DID_JSON | DID_LD_JSON => match serde_json::to_vec(document) {
+            Ok(val) => Ok(val),
+            Err(error) => Err(Error::new(error.to_string().as_str())),
+        },
+        _ => Err(Error::new(
+            format!(
+                "The DID resolver does not support the requested 'accept' format: {}",
+                accept
+            )
+            .as_str(),
+        )),
+    }
+}
+
+pub fn to_cbor(value: &Value) -> Vec<u8> {
+    let mut result = Vec::new();
+    write_cbor(value, &mut result);
+    result
+}
+
+fn write_cbor(value: &Value, result: &mut Vec<u8>) {
+    match value {
+        Value::Null => result.push(0xf6),
+        Value::Bool(false) => result.push(0xf4),
+        Value::Bool(true) => result.push(0xf5),
+        Value::Number(number) => {
+            if let Some(val) = number.as_u64() {
+                write_head(0, val, result);
+            } else if let Some(val) = number.as_i64() {
+                write_head(1, (-1 - val) as u64, result);
+            } else if let Some(val) = number.as_f64() {
+                write_float(val, result);
+            }
+        }
+        Value::String(val) => {
+            write_head(3, val.len() as u64, result);
+            result.extend_from_slice(val.as_bytes());
+        }
+        Value::Array(items) => {
+            write_head(4, items.len() as u64, result);
+            for item in items {
+                write_cbor(item, result);
+            }
+        }
+        Value::Object(map) => {
+            let mut entries = map
+                .iter()
+                .map(|(key, val)| (to_cbor(&Value::String(key.clone())), val))
+                .collect::<Vec<(Vec<u8>, &Value)>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            write_head(5, entries.len() as u64, result);
+            for (key, val) in entries {
+                result.extend(key);
+                write_cbor(val, result);
+            }
+        }
+    }
+}
+
+fn write_head(major: u8, value: u64, result: &mut Vec<u8>) {
+    let major = major << 5;
+
+    match value {
+        0..=23 => result.push(major | value as u8),
+        24..=0xff => {
+            result.push(major | 24);
+            result.push(value as u8);
+        }
+        0x100..=0xffff => {
+            result.push(major | 25);
+            result.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x10000..=0xffff_ffff => {
+            result.push(major | 26);
+            result.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            result.push(major | 27);
+            result.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn write_float(value: f64, result: &mut Vec<u8>) {
+    if let Some(val) = to_half(value) {
+        result.push(0xf9);
+        result.extend_from_slice(&val.to_be_bytes());
+    } else if (value as f32) as f64 == value {
+        result.push(0xfa);
+        result.extend_from_slice(&(value as f32).to_be_bytes());
+    } else {
+        result.push(0xfb);
+        result.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn to_half(value: f64) -> Option<u16> {
+    let sign: u16 = match value.is_sign_negative() {
+        true => 0x8000,
+        false => 0,
+    };
+
+    if value.is_nan() {
+        return Some(0x7e00);
+    }
+
+    if value.is_infinite() {
+        return Some(sign | 0x7c00);
+    }
+
+    let magnitude = value.abs();
+    if magnitude == 0.0 {
+        return Some(sign);
+    }
+
+    let exponent = magnitude.log2().floor() as i32;
+    if exponent < -14 {
+        let mantissa = magnitude * 2f64.powi(24);
+        return match mantissa.fract() == 0.0 && mantissa < 1024.0 {
+            true => Some(sign | mantissa as u16),
+            false => None,
+        };
+    }
+
+    if exponent > 15 {
+        return None;
+    }
+
+    let mantissa = (magnitude / 2f64.powi(exponent) - 1.0) * 1024.0;
+    match mantissa.fract() == 0.0 {
+        true => Some(sign | (((exponent + 15) as u16) << 10) | mantissa as u16),
+        false => None,
+    }
+}
@@ -0,0 +1,67 @@
+use fi_common::{did::DidDocument, error::Error};
+use serde::ser::{Error as _, SerializeStruct};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+
+use crate::profile::OutputProfile;
+use crate::representation::represent;
+use crate::to_profile_value;
+
+#[derive(Clone)]
+pub struct DidResolutionResult {
+    pub did_resolution_metadata: DidResolutionMetadata,
+    pub did_document: DidDocument,
+    pub did_document_metadata: DidDocumentMetadata,
+    pub profile: OutputProfile,
+}
+
+impl DidResolutionResult {
+    pub fn document(&self) -> Result<Value, Error> {
+        to_profile_value(
+            &self.did_document,
+            self.did_resolution_metadata.content_type.as_str(),
+            &self.profile,
+        )
+    }
+
+    pub fn representation(&self) -> Result<Vec<u8>, Error> {
+        represent(
+            &self.document()?,
+            self.did_resolution_metadata.content_type.as_str(),
+        )
+    }
+}
+
+impl Serialize for DidResolutionResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let document = match self.document() {
+            Ok(val) => val,
+            Err(error) => return Err(S::Error::custom(error.to_string())),
+        };
+
+        let mut result = serializer.serialize_struct("DidResolutionResult", 3)?;
+        result.serialize_field("didResolutionMetadata", &self.did_resolution_metadata)?;
+        result.serialize_field("didDocument", &document)?;
+        result.serialize_field("didDocumentMetadata", &self.did_document_metadata)?;
+        result.end()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionMetadata {
+    pub content_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invalid_keys: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<bool>,
+}
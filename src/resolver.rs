@@ -0,0 +1,917 @@
+use ethers::providers::{Http, JsonRpcClient, Middleware, Provider};
+use ethers::types::{BlockNumber, H160, H256};
+use fi_common::{did::DidDocument, error::Error};
+use futures::channel::oneshot;
+use futures::{stream, Stream, StreamExt};
+use instant::Instant;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::caip::{ethr_to_pkh, AccountId};
+use crate::ethr::{
+    build_did_doc_from_fetched_logs, get_block_timestamp, get_http, get_identity_logs,
+    get_identity_owner, get_latest_timestamp, get_registry_history, get_registry_logs,
+    get_valid_delegate, new_client, parse_address, to_bytes32, DEFAULT_REGISTRY,
+};
+use crate::history::RegistryEvent;
+use crate::indexer::{references_address, AddressLink, RegistryIndex};
+use crate::profile::OutputProfile;
+use crate::proof::get_verified_logs;
+use crate::resolution::DidResolutionResult;
+use crate::router::{DidResolver, ResolutionFuture};
+use crate::transport::{RateLimiter, RpcPolicy, RpcTransport};
+use crate::{get_identity_address, get_network, new_document, to_resolution_result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub name: String,
+    pub provider_url: String,
+    pub registry: Option<String>,
+    pub chain_id: Option<u64>,
+}
+
+impl NetworkConfig {
+    pub fn new(name: &str, provider_url: &str) -> NetworkConfig {
+        NetworkConfig {
+            name: String::from(name),
+            provider_url: String::from(provider_url),
+            registry: None,
+            chain_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EthrResolverBuilder {
+    networks: Vec<NetworkConfig>,
+    policy: RpcPolicy,
+    rate_limit: Option<(u32, u32)>,
+    cache_ttl: Option<Duration>,
+    consistency_check: bool,
+    profile: OutputProfile,
+}
+
+impl EthrResolverBuilder {
+    pub fn network(mut self, network: NetworkConfig) -> EthrResolverBuilder {
+        self.networks.push(network);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> EthrResolverBuilder {
+        self.policy.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retries(mut self, max_retries: u32, initial_backoff: Duration) -> EthrResolverBuilder {
+        self.policy.max_retries = max_retries;
+        self.policy.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn rate_limit(mut self, requests_per_second: u32, burst: u32) -> EthrResolverBuilder {
+        self.rate_limit = Some((requests_per_second, burst));
+        self
+    }
+
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> EthrResolverBuilder {
+        self.cache_ttl = Some(cache_ttl);
+        self
+    }
+
+    /// Cross-checks resolved documents against the registry's `identityOwner`
+    /// and `validDelegate` views. Only the owner and the veriKey delegates
+    /// left in the document are checked, so a provider that omits a delegate
+    /// event entirely is not detected.
+    pub fn consistency_check(mut self, consistency_check: bool) -> EthrResolverBuilder {
+        self.consistency_check = consistency_check;
+        self
+    }
+
+    pub fn profile(mut self, profile: OutputProfile) -> EthrResolverBuilder {
+        self.profile = profile;
+        self
+    }
+
+    pub fn build(self) -> Result<EthrResolver, Error> {
+        self.build_with(|network| get_http(network.provider_url.as_str()))
+    }
+
+    pub fn build_with<C, F>(self, mut connect: F) -> Result<EthrResolver<C>, Error>
+    where
+        C: JsonRpcClient + 'static,
+        F: FnMut(&NetworkConfig) -> Result<C, Error>,
+    {
+        if self.networks.is_empty() {
+            return Err(Error::new("The DID resolver has no networks configured"));
+        }
+
+        let rate_limiter = self.rate_limit.map(|(requests_per_second, burst)| {
+            Arc::new(RateLimiter::new(requests_per_second, burst))
+        });
+
+        let mut networks = Vec::<Network<C>>::new();
+
+        for config in self.networks {
+            if networks.iter().any(|network| network.name == config.name) {
+                return Err(Error::new(
+                    format!("Network is configured more than once: {}", config.name).as_str(),
+                ));
+            }
+
+            let registry = parse_address(match &config.registry {
+                Some(val) => val.as_str(),
+                None => DEFAULT_REGISTRY,
+            })?;
+
+            let client = new_client(connect(&config)?, self.policy, rate_limiter.clone());
+
+            let chain_id = OnceLock::new();
+            if let Some(val) = config.chain_id {
+                let _ = chain_id.set(val);
+            }
+
+            networks.push(Network {
+                name: config.name,
+                chain_id,
+                registry,
+                client,
+            });
+        }
+
+        Ok(EthrResolver {
+            state: Arc::new(ResolverState {
+                networks,
+                profile: self.profile,
+                cache_ttl: self.cache_ttl,
+                consistency_check: self.consistency_check,
+                cache: Mutex::new(HashMap::new()),
+                in_flight: Mutex::new(HashMap::new()),
+                chain_id_lookups: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+}
+
+type Waiters<T = DidResolutionResult> = Vec<oneshot::Sender<Result<T, String>>>;
+
+struct Network<C> {
+    name: String,
+    chain_id: OnceLock<u64>,
+    registry: H160,
+    client: Arc<Provider<RpcTransport<C>>>,
+}
+
+struct ResolverState<C> {
+    networks: Vec<Network<C>>,
+    profile: OutputProfile,
+    cache_ttl: Option<Duration>,
+    consistency_check: bool,
+    cache: Mutex<HashMap<(String, String), (Instant, DidResolutionResult)>>,
+    in_flight: Mutex<HashMap<(String, String), Waiters>>,
+    chain_id_lookups: Mutex<HashMap<String, Waiters<u64>>>,
+}
+
+struct InFlight<'a, K: Eq + Hash, T> {
+    in_flight: &'a Mutex<HashMap<K, Waiters<T>>>,
+    key: K,
+    completed: bool,
+}
+
+impl<K: Eq + Hash, T: Clone> InFlight<'_, K, T> {
+    fn complete(mut self, result: &Result<T, Error>) {
+        self.completed = true;
+
+        for waiter in self.take() {
+            let _ = waiter.send(match result {
+                Ok(val) => Ok(val.clone()),
+                Err(error) => Err(error.to_string()),
+            });
+        }
+    }
+}
+
+impl<K: Eq + Hash, T> InFlight<'_, K, T> {
+    fn take(&self) -> Waiters<T> {
+        match self.in_flight.lock() {
+            Ok(mut val) => val.remove(&self.key).unwrap_or_default(),
+            Err(_error) => Vec::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, T> Drop for InFlight<'_, K, T> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.take();
+        }
+    }
+}
+
+pub struct EthrResolver<C = Http> {
+    state: Arc<ResolverState<C>>,
+}
+
+impl<C> Clone for EthrResolver<C> {
+    fn clone(&self) -> EthrResolver<C> {
+        EthrResolver {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl EthrResolver {
+    pub fn builder() -> EthrResolverBuilder {
+        EthrResolverBuilder::default()
+    }
+}
+
+impl<C: JsonRpcClient + 'static> EthrResolver<C> {
+    pub async fn resolve(&self, did: &str, accept: &str) -> Result<Value, Error> {
+        match self.resolve_with_metadata(did, accept).await {
+            Ok(val) => val.document(),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub async fn resolve_with_metadata(
+        &self,
+        did: &str,
+        accept: &str,
+    ) -> Result<DidResolutionResult, Error> {
+        let key = (String::from(did), String::from(accept));
+
+        if let Some(val) = self.cached(&key) {
+            return Ok(val);
+        }
+
+        while let Some(receiver) = join_in_flight(&self.state.in_flight, &key) {
+            match receiver.await {
+                Ok(Ok(val)) => return Ok(val),
+                Ok(Err(error)) => return Err(Error::new(error.as_str())),
+                Err(_canceled) => continue,
+            }
+        }
+
+        let in_flight = InFlight {
+            in_flight: &self.state.in_flight,
+            key,
+            completed: false,
+        };
+
+        let result = self.resolve_uncached(did, accept).await;
+
+        if let Ok(val) = &result {
+            self.store(in_flight.key.clone(), val);
+        }
+
+        in_flight.complete(&result);
+        result
+    }
+
+    pub fn resolve_many<'a, I>(
+        &'a self,
+        dids: I,
+        accept: &'a str,
+        concurrency: usize,
+    ) -> impl Stream<Item = (String, Result<DidResolutionResult, Error>)> + 'a
+    where
+        I: IntoIterator<Item = String>,
+        I::IntoIter: 'a,
+    {
+        stream::iter(dids)
+            .map(move |did| async move {
+                let result = self.resolve_with_metadata(did.as_str(), accept).await;
+                (did, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    pub async fn dereference(&self, did_url: &str, accept: &str) -> Result<Value, Error> {
+        let (did_part, fragment) = match did_url.split_once('#') {
+            Some((did_part, fragment)) => (did_part, Some(fragment)),
+            None => (did_url, None),
+        };
+
+        let (did, query) = match did_part.split_once('?') {
+            Some((did, query)) => (did, Some(query)),
+            None => (did_part, None),
+        };
+
+        let document = match self.resolve_with_metadata(did, accept).await {
+            Ok(val) => match val.document() {
+                Ok(val) => val,
+                Err(error) => return Err(error),
+            },
+            Err(error) => return Err(error),
+        };
+
+        let dereferenced = match (query, fragment) {
+            (None, None) => Some(document),
+            (None, Some(fragment)) => find_resource(&document, &format!("{}#{}", did, fragment)),
+            (Some(query), fragment) => match service_endpoint(&document, did, query) {
+                Ok(val) => val.map(|endpoint| match (endpoint, fragment) {
+                    (Value::String(endpoint), Some(fragment)) => {
+                        Value::String(format!("{}#{}", endpoint, fragment))
+                    }
+                    (endpoint, _) => endpoint,
+                }),
+                Err(error) => return Err(error),
+            },
+        };
+
+        match dereferenced {
+            Some(val) => Ok(val),
+            None => Err(Error::new(
+                format!("The DID URL could not be dereferenced: {}", did_url).as_str(),
+            )),
+        }
+    }
+
+    pub async fn history(&self, did: &str) -> Result<Vec<RegistryEvent>, Error> {
+        let address = match get_identity_address(did) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let network = match self.network(get_network(did).as_str()).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        get_registry_history(&network.client, network.registry, address.as_str()).await
+    }
+
+    pub async fn to_pkh(&self, did: &str) -> Result<String, Error> {
+        let network = match self.network(get_network(did).as_str()).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let chain_id = match self.chain_id(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        ethr_to_pkh(did, Some(chain_id))
+    }
+
+    /// Resolves from logs proven against `block_hash`. Headers are walked back
+    /// from the nearest trusted block (`block_hash`, a `checkpoint` or an
+    /// already linked change block), at most `MAX_HEADER_WALK` (128) per
+    /// change; a change further back fails unless a closer checkpoint is given.
+    pub async fn resolve_verified(
+        &self,
+        did: &str,
+        accept: &str,
+        block_hash: H256,
+        checkpoints: &[H256],
+    ) -> Result<DidResolutionResult, Error> {
+        let mut did_doc = match new_document(did, accept) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let address = match get_identity_address(did) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let (network, identity) = match self.identity(did).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let chain_id = match self.chain_id(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let (logs, timestamp) = match get_verified_logs(
+            &network.client,
+            network.registry,
+            identity,
+            block_hash,
+            checkpoints,
+        )
+        .await
+        {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        match build_did_doc_from_fetched_logs(
+            address.as_str(),
+            &mut did_doc,
+            chain_id,
+            Some(timestamp),
+            logs,
+            &self.state.profile,
+        ) {
+            Ok(val) => to_resolution_result(accept, val, &self.state.profile),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub async fn owner_of(&self, did: &str) -> Result<String, Error> {
+        let (network, identity) = match self.identity(did).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        match get_identity_owner(&network.client, network.registry, identity).await {
+            Ok(val) => Ok(format!("{:#x}", val)),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub async fn is_valid_delegate(
+        &self,
+        did: &str,
+        delegate_type: &str,
+        delegate: &str,
+    ) -> Result<bool, Error> {
+        let delegate_type = match to_bytes32(delegate_type) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let delegate = match parse_address(delegate) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let (network, identity) = match self.identity(did).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        get_valid_delegate(
+            &network.client,
+            network.registry,
+            identity,
+            delegate_type,
+            delegate,
+        )
+        .await
+    }
+
+    pub async fn new_index(&self, network: &str) -> Result<RegistryIndex, Error> {
+        let network = match self.network(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let mut index = match self.chain_id(network).await {
+            Ok(val) => {
+                RegistryIndex::new(Some(network.name.as_str()), val, self.state.profile.clone())
+            }
+            Err(error) => return Err(error),
+        };
+        index.set_registry(network.registry);
+
+        Ok(index)
+    }
+
+    pub async fn index(
+        &self,
+        index: &mut RegistryIndex,
+        network: &str,
+        from_block: u64,
+        to_block: Option<u64>,
+    ) -> Result<Vec<String>, Error> {
+        let network = match self.network(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let to_block = match to_block {
+            Some(val) => val,
+            None => match network.client.get_block_number().await {
+                Ok(val) => val.as_u64(),
+                Err(error) => return Err(Error::new(error.to_string().as_str())),
+            },
+        };
+
+        let logs = match get_registry_logs(&network.client, network.registry, from_block, to_block)
+            .await
+        {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let timestamp = match get_block_timestamp(&network.client, to_block.into()).await {
+            Ok(Some(val)) => val,
+            Ok(None) => {
+                return Err(Error::new(
+                    format!("Block {} could not be fetched", to_block).as_str(),
+                ))
+            }
+            Err(error) => return Err(error),
+        };
+
+        let updated = index.ingest(logs, timestamp);
+        index.set_last_block(to_block);
+
+        Ok(updated)
+    }
+
+    pub async fn reverse_lookup(
+        &self,
+        network: &str,
+        address: &str,
+        from_block: u64,
+    ) -> Result<Vec<AddressLink>, Error> {
+        let target = match parse_address(address) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let network = match self.network(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let chain_id = match self.chain_id(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let (latest, timestamp) = match network.client.get_block(BlockNumber::Latest).await {
+            Ok(Some(block)) => match block.number {
+                Some(number) => (number.as_u64(), block.timestamp.as_u64()),
+                None => return Err(Error::new("The latest block has no number")),
+            },
+            Ok(None) => return Err(Error::new("The latest block could not be fetched")),
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        let logs =
+            match get_registry_logs(&network.client, network.registry, from_block, latest).await {
+                Ok(val) => val,
+                Err(error) => return Err(error),
+            };
+
+        let identities = logs
+            .iter()
+            .filter(|log| references_address(log, target))
+            .filter_map(|log| log.topics.get(1).map(|topic| H160::from(*topic)))
+            .collect::<HashSet<H160>>();
+
+        let mut index = RegistryIndex::new(
+            Some(network.name.as_str()),
+            chain_id,
+            self.state.profile.clone(),
+        );
+        index.set_registry(network.registry);
+        index.ingest(
+            logs.into_iter()
+                .filter(|log| {
+                    log.topics
+                        .get(1)
+                        .is_some_and(|topic| identities.contains(&H160::from(*topic)))
+                })
+                .collect(),
+            timestamp,
+        );
+
+        index.links(address, timestamp)
+    }
+
+    async fn resolve_uncached(
+        &self,
+        did: &str,
+        accept: &str,
+    ) -> Result<DidResolutionResult, Error> {
+        let mut did_doc = match new_document(did, accept) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let address = match get_identity_address(did) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let network = match self.network(get_network(did).as_str()).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let chain_id = match self.chain_id(network).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let identity = match parse_address(address.as_str()) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let logs = match get_identity_logs(&network.client, network.registry, identity).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let timestamp = match get_latest_timestamp(&network.client).await {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        let result = match build_did_doc_from_fetched_logs(
+            address.as_str(),
+            &mut did_doc,
+            chain_id,
+            Some(timestamp),
+            logs,
+            &self.state.profile,
+        ) {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        if self.state.consistency_check {
+            match check_consistency(network, identity, &result.0, result.1).await {
+                Ok(()) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        to_resolution_result(accept, result, &self.state.profile)
+    }
+
+    async fn identity(&self, did: &str) -> Result<(&Network<C>, H160), Error> {
+        let address = get_identity_address(did)?;
+        let network = self.network(get_network(did).as_str()).await?;
+
+        Ok((network, parse_address(address.as_str())?))
+    }
+
+    async fn chain_id(&self, network: &Network<C>) -> Result<u64, Error> {
+        if let Some(val) = network.chain_id.get() {
+            return Ok(*val);
+        }
+
+        while let Some(receiver) = join_in_flight(&self.state.chain_id_lookups, &network.name) {
+            match receiver.await {
+                Ok(Ok(val)) => return Ok(val),
+                Ok(Err(error)) => return Err(Error::new(error.as_str())),
+                Err(_canceled) => continue,
+            }
+        }
+
+        let in_flight = InFlight {
+            in_flight: &self.state.chain_id_lookups,
+            key: network.name.clone(),
+            completed: false,
+        };
+
+        let result = match network.chain_id.get() {
+            Some(val) => Ok(*val),
+            None => match network.client.get_chainid().await {
+                Ok(val) => Ok(val.as_u64()),
+                Err(error) => Err(Error::new(error.to_string().as_str())),
+            },
+        };
+
+        if let Ok(val) = &result {
+            let _ = network.chain_id.set(*val);
+        }
+
+        in_flight.complete(&result);
+        result
+    }
+
+    async fn network(&self, name: &str) -> Result<&Network<C>, Error> {
+        let not_configured = || {
+            Error::new(format!("The DID resolver is not configured for network: {}", name).as_str())
+        };
+
+        if let Some(network) = self
+            .state
+            .networks
+            .iter()
+            .find(|network| network.name == name)
+        {
+            return Ok(network);
+        }
+
+        let chain_id = match name
+            .strip_prefix("0x")
+            .and_then(|val| u64::from_str_radix(val, 16).ok())
+        {
+            Some(val) => val,
+            None => return Err(not_configured()),
+        };
+
+        if let Some(network) = self
+            .state
+            .networks
+            .iter()
+            .find(|network| network.chain_id.get() == Some(&chain_id))
+        {
+            return Ok(network);
+        }
+
+        for network in &self.state.networks {
+            if network.chain_id.get().is_none()
+                && self.chain_id(network).await.ok() == Some(chain_id)
+            {
+                return Ok(network);
+            }
+        }
+
+        Err(not_configured())
+    }
+
+    fn cached(&self, key: &(String, String)) -> Option<DidResolutionResult> {
+        let cache_ttl = self.state.cache_ttl?;
+
+        let mut cache = match self.state.cache.lock() {
+            Ok(val) => val,
+            Err(_error) => return None,
+        };
+
+        match cache.get(key) {
+            Some((stored, result)) if stored.elapsed() < cache_ttl => Some(result.clone()),
+            Some(_val) => {
+                cache.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn store(&self, key: (String, String), result: &DidResolutionResult) {
+        if self.state.cache_ttl.is_none() {
+            return;
+        }
+
+        if let Ok(mut cache) = self.state.cache.lock() {
+            cache.insert(key, (Instant::now(), result.clone()));
+        }
+    }
+}
+
+impl<C: JsonRpcClient + 'static> DidResolver for EthrResolver<C> {
+    fn resolve<'a>(&'a self, did: &'a str, accept: &'a str) -> ResolutionFuture<'a> {
+        Box::pin(self.resolve_with_metadata(did, accept))
+    }
+}
+
+fn join_in_flight<K: Eq + Hash + Clone, T>(
+    in_flight: &Mutex<HashMap<K, Waiters<T>>>,
+    key: &K,
+) -> Option<oneshot::Receiver<Result<T, String>>> {
+    let mut in_flight = match in_flight.lock() {
+        Ok(val) => val,
+        Err(_error) => return None,
+    };
+
+    match in_flight.get_mut(key) {
+        Some(waiters) => {
+            let (sender, receiver) = oneshot::channel();
+            waiters.push(sender);
+            Some(receiver)
+        }
+        None => {
+            in_flight.insert(key.clone(), Vec::new());
+            None
+        }
+    }
+}
+
+async fn check_consistency<C: JsonRpcClient + 'static>(
+    network: &Network<C>,
+    identity: H160,
+    document: &DidDocument,
+    deactivated: bool,
+) -> Result<(), Error> {
+    let methods = match &document.verification_method {
+        Some(val) => val.as_slice(),
+        None => &[],
+    };
+
+    let owner = match deactivated {
+        true => Some(H160::zero()),
+        false => methods
+            .iter()
+            .find(|method| {
+                method
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| id.ends_with("#controller"))
+            })
+            .and_then(|method| method_address(method.blockchain_account_id.as_deref())),
+    };
+
+    let on_chain_owner = match get_identity_owner(&network.client, network.registry, identity).await
+    {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    if owner != Some(on_chain_owner) {
+        return Err(Error::new(
+            format!(
+                "The registry logs for {} are inconsistent: the owner is {:#x} on-chain",
+                document.id, on_chain_owner
+            )
+            .as_str(),
+        ));
+    }
+
+    let delegate_type = match to_bytes32("veriKey") {
+        Ok(val) => val,
+        Err(error) => return Err(error),
+    };
+
+    for method in methods {
+        let is_delegate = method
+            .id
+            .as_ref()
+            .is_some_and(|id| id.contains("#delegate-"));
+
+        let delegate = match method_address(method.blockchain_account_id.as_deref()) {
+            Some(val) if is_delegate => val,
+            _ => continue,
+        };
+
+        let valid = match get_valid_delegate(
+            &network.client,
+            network.registry,
+            identity,
+            delegate_type,
+            delegate,
+        )
+        .await
+        {
+            Ok(val) => val,
+            Err(error) => return Err(error),
+        };
+
+        if !valid {
+            return Err(Error::new(
+                format!(
+                    "The registry logs for {} are inconsistent: {:#x} is not a valid veriKey delegate on-chain",
+                    document.id, delegate
+                )
+                .as_str(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn method_address(blockchain_account_id: Option<&str>) -> Option<H160> {
+    blockchain_account_id
+        .and_then(|val| AccountId::parse(val).ok())
+        .and_then(|val| parse_address(val.address.as_str()).ok())
+}
+
+fn find_resource(document: &Value, id: &str) -> Option<Value> {
+    ["verificationMethod", "keyAgreement", "service"]
+        .iter()
+        .filter_map(|property| document[*property].as_array())
+        .flatten()
+        .find(|resource| resource["id"].as_str() == Some(id))
+        .cloned()
+}
+
+fn service_endpoint(document: &Value, did: &str, query: &str) -> Result<Option<Value>, Error> {
+    let mut service = None;
+    let mut relative_ref = None;
+
+    for parameter in query.split('&') {
+        match parameter.split_once('=') {
+            Some(("service", val)) => service = Some(val),
+            Some(("relativeRef", val)) => relative_ref = Some(val),
+            _ => {
+                return Err(Error::new(
+                    format!("Unsupported DID URL parameter: {}", parameter).as_str(),
+                ))
+            }
+        };
+    }
+
+    let service = match service {
+        Some(val) => val,
+        None => return Ok(None),
+    };
+
+    let endpoint = match find_resource(document, &format!("{}#{}", did, service)) {
+        Some(val) => val["serviceEndpoint"].clone(),
+        None => return Ok(None),
+    };
+
+    Ok(match (endpoint, relative_ref) {
+        (Value::String(endpoint), Some(relative_ref)) => {
+            Some(Value::String(format!("{}{}", endpoint, relative_ref)))
+        }
+        (Value::Null, _) => None,
+        (endpoint, _) => Some(endpoint),
+    })
+}
@@ -0,0 +1,93 @@
+use fi_common::error::Error;
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::BoxFuture;
+#[cfg(target_arch = "wasm32")]
+use futures::future::LocalBoxFuture;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::resolution::DidResolutionResult;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type ResolutionFuture<'a> = BoxFuture<'a, Result<DidResolutionResult, Error>>;
+#[cfg(target_arch = "wasm32")]
+pub type ResolutionFuture<'a> = LocalBoxFuture<'a, Result<DidResolutionResult, Error>>;
+
+pub trait DidResolver: Send + Sync {
+    fn resolve<'a>(&'a self, did: &'a str, accept: &'a str) -> ResolutionFuture<'a>;
+}
+
+#[derive(Clone, Default)]
+pub struct DidMethodRouter {
+    resolvers: HashMap<String, Arc<dyn DidResolver>>,
+}
+
+impl DidMethodRouter {
+    pub fn new() -> DidMethodRouter {
+        DidMethodRouter::default()
+    }
+
+    pub fn register(&mut self, method: &str, resolver: Arc<dyn DidResolver>) -> Result<(), Error> {
+        if !is_method_name(method) {
+            return Err(Error::new(
+                format!("Not a valid DID method name: {}", method).as_str(),
+            ));
+        }
+
+        self.resolvers.insert(String::from(method), resolver);
+        Ok(())
+    }
+
+    pub fn methods(&self) -> Vec<String> {
+        let mut methods = self.resolvers.keys().cloned().collect::<Vec<String>>();
+        methods.sort();
+        methods
+    }
+
+    pub async fn resolve(&self, did: &str, accept: &str) -> Result<DidResolutionResult, Error> {
+        let method = match did_method(did) {
+            Some(val) => val,
+            None => return Err(Error::new(format!("Not a valid DID: {}", did).as_str())),
+        };
+
+        match self.resolvers.get(method) {
+            Some(resolver) => resolver.resolve(did, accept).await,
+            None => Err(Error::new(
+                format!("The DID method is not supported: {}", method).as_str(),
+            )),
+        }
+    }
+}
+
+impl DidResolver for DidMethodRouter {
+    fn resolve<'a>(&'a self, did: &'a str, accept: &'a str) -> ResolutionFuture<'a> {
+        Box::pin(DidMethodRouter::resolve(self, did, accept))
+    }
+}
+
+impl Debug for DidMethodRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.methods()).finish()
+    }
+}
+
+fn did_method(did: &str) -> Option<&str> {
+    let mut components = did.splitn(3, ':');
+
+    match (components.next(), components.next(), components.next()) {
+        (Some("did"), Some(method), Some(identifier))
+            if is_method_name(method) && !identifier.is_empty() =>
+        {
+            Some(method)
+        }
+        _ => None,
+    }
+}
+
+fn is_method_name(method: &str) -> bool {
+    !method.is_empty()
+        && method
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
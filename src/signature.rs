@@ -0,0 +1,169 @@
+use base64::Engine;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId, Signature};
+use secp256k1::{Message, PublicKey, Secp256k1};
+use serde_json::Value;
+
+use crate::util::{public_key_to_address, strip0x};
+use crate::verification::ECDSA_SECP256K1_RECOVERY_METHOD2020;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofPurpose {
+    Authentication,
+    AssertionMethod,
+}
+
+impl ProofPurpose {
+    pub fn relationship(&self) -> &'static str {
+        match self {
+            ProofPurpose::Authentication => "authentication",
+            ProofPurpose::AssertionMethod => "assertionMethod",
+        }
+    }
+}
+
+pub fn candidate_methods(doc: &Value, purpose: ProofPurpose, kid: Option<&str>) -> Vec<Value> {
+    let did = doc["id"].as_str().unwrap_or("");
+
+    let references = match doc[purpose.relationship()].as_array() {
+        Some(val) => val.clone(),
+        None => Vec::new(),
+    };
+
+    let methods = match doc["verificationMethod"].as_array() {
+        Some(val) => val.clone(),
+        None => Vec::new(),
+    };
+
+    references
+        .iter()
+        .filter_map(|reference| match reference {
+            Value::String(id) => methods
+                .iter()
+                .find(|method| method["id"].as_str().is_some_and(|val| val.eq(id)))
+                .cloned(),
+            Value::Object(_) => Some(reference.clone()),
+            _ => None,
+        })
+        .filter(|method| !method["revoked"].as_bool().unwrap_or(false))
+        .filter(|method| match kid {
+            Some(kid) => method["id"].as_str().is_some_and(|id| {
+                id.eq(kid) || (kid.starts_with('#') && id.eq(&format!("{}{}", did, kid)))
+            }),
+            None => true,
+        })
+        .collect()
+}
+
+pub fn recovery_methods(doc: &Value) -> Vec<Value> {
+    match doc["verificationMethod"].as_array() {
+        Some(val) => val
+            .iter()
+            .filter(|method| {
+                method["type"].as_str() == Some(ECDSA_SECP256K1_RECOVERY_METHOD2020)
+                    && !method["revoked"].as_bool().unwrap_or(false)
+            })
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+pub fn method_public_key(method: &Value) -> Option<PublicKey> {
+    if let Some(public_key_hex) = method["publicKeyHex"].as_str() {
+        let bytes = hex::decode(strip0x(String::from(public_key_hex))).ok()?;
+        return PublicKey::from_slice(&bytes).ok();
+    }
+
+    let jwk = &method["publicKeyJwk"];
+    if jwk["kty"].as_str() == Some("EC") && jwk["crv"].as_str() == Some("secp256k1") {
+        let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        let x = engine.decode(jwk["x"].as_str()?).ok()?;
+        let y = engine.decode(jwk["y"].as_str()?).ok()?;
+
+        let mut bytes = vec![0x04];
+        bytes.extend(x);
+        bytes.extend(y);
+        return PublicKey::from_slice(&bytes).ok();
+    }
+
+    None
+}
+
+pub fn method_address(method: &Value) -> Option<String> {
+    if let Some(account_id) = method["blockchainAccountId"].as_str() {
+        let address = account_id.split(':').next_back()?.split('@').next()?;
+        return Some(address.to_lowercase());
+    }
+
+    method["ethereumAddress"]
+        .as_str()
+        .map(|address| address.to_lowercase())
+}
+
+pub fn recover_public_key(
+    digest: [u8; 32],
+    signature: &[u8],
+    recovery_id: i32,
+) -> Option<PublicKey> {
+    let recovery_id = RecoveryId::from_i32(recovery_id).ok()?;
+    let signature = RecoverableSignature::from_compact(signature, recovery_id).ok()?;
+
+    Secp256k1::verification_only()
+        .recover_ecdsa(&Message::from_digest(digest), &signature)
+        .ok()
+}
+
+pub fn normalize_recovery_id(v: u8) -> Option<i32> {
+    match v {
+        0 | 1 => Some(v as i32),
+        27 | 28 => Some((v - 27) as i32),
+        _ => None,
+    }
+}
+
+pub fn verify_with_method(
+    method: &Value,
+    digest: [u8; 32],
+    signature: &[u8],
+    recovery_id: Option<i32>,
+) -> bool {
+    if signature.len() != 64 {
+        return false;
+    }
+
+    let recovered = match recovery_id {
+        Some(id) => vec![recover_public_key(digest, signature, id)],
+        None => vec![
+            recover_public_key(digest, signature, 0),
+            recover_public_key(digest, signature, 1),
+        ],
+    }
+    .into_iter()
+    .flatten()
+    .collect::<Vec<PublicKey>>();
+
+    if let Some(public_key) = method_public_key(method) {
+        if recovery_id.is_some() {
+            return recovered.contains(&public_key);
+        }
+
+        let mut signature = match Signature::from_compact(signature) {
+            Ok(val) => val,
+            Err(_error) => return false,
+        };
+        // JOSE does not require low-S ES256K signatures and common did-jwt
+        // signers emit both forms, so high-S values are accepted here.
+        signature.normalize_s();
+
+        return Secp256k1::verification_only()
+            .verify_ecdsa(&Message::from_digest(digest), &signature, &public_key)
+            .is_ok();
+    }
+
+    match method_address(method) {
+        Some(address) => recovered
+            .iter()
+            .any(|public_key| public_key_to_address(public_key).eq(&address)),
+        None => false,
+    }
+}
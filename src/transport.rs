@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use ethers::providers::{Http, JsonRpcClient, ProviderError, RpcError};
+use futures::future::{select, Either};
+use futures_timer::Delay;
+use instant::Instant;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TOO_MANY_REQUESTS: i64 = 429;
+const LIMIT_EXCEEDED: i64 = -32005;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcPolicy {
+    pub timeout: Option<Duration>,
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RpcPolicy {
+    fn default() -> RpcPolicy {
+        RpcPolicy {
+            timeout: Some(Duration::from_secs(30)),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RpcPolicy {
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32, burst: u32) -> RateLimiter {
+        let capacity = f64::from(burst.max(1));
+
+        RateLimiter {
+            capacity,
+            refill_per_second: f64::from(requests_per_second.max(1)),
+            bucket: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    pub async fn acquire(&self) {
+        while let Some(wait) = self.take() {
+            Delay::new(wait).await;
+        }
+    }
+
+    fn take(&self) -> Option<Duration> {
+        let mut bucket = match self.bucket.lock() {
+            Ok(val) => val,
+            Err(error) => error.into_inner(),
+        };
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.1).as_secs_f64();
+        let tokens = (bucket.0 + elapsed * self.refill_per_second).min(self.capacity);
+
+        match tokens >= 1.0 {
+            true => {
+                *bucket = (tokens - 1.0, now);
+                None
+            }
+            false => {
+                *bucket = (tokens, now);
+                Some(Duration::from_secs_f64(
+                    (1.0 - tokens) / self.refill_per_second,
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RpcTransport<C = Http> {
+    inner: C,
+    policy: RpcPolicy,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<C: JsonRpcClient> RpcTransport<C> {
+    pub fn new(
+        inner: C,
+        policy: RpcPolicy,
+        rate_limiter: Option<Arc<RateLimiter>>,
+    ) -> RpcTransport<C> {
+        RpcTransport {
+            inner,
+            policy,
+            rate_limiter,
+        }
+    }
+
+    async fn send<R: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<R, ProviderError> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        let request = self.inner.request::<_, R>(method, params);
+
+        match self.policy.timeout {
+            Some(timeout) => match select(Box::pin(request), Delay::new(timeout)).await {
+                Either::Left((result, _delay)) => result.map_err(Into::into),
+                Either::Right(_timeout) => Err(ProviderError::CustomError(format!(
+                    "The JSON-RPC request {} timed out after {}ms",
+                    method,
+                    timeout.as_millis()
+                ))),
+            },
+            None => request.await.map_err(Into::into),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: JsonRpcClient> JsonRpcClient for RpcTransport<C> {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = match serde_json::to_value(params) {
+            Ok(val) => val,
+            Err(error) => return Err(ProviderError::SerdeJson(error)),
+        };
+
+        let mut attempt = 0;
+
+        loop {
+            match self.send::<R>(method, &params).await {
+                Ok(val) => return Ok(val),
+                Err(error) => {
+                    if attempt >= self.policy.max_retries || !is_transient(&error) {
+                        return Err(error);
+                    }
+
+                    Delay::new(self.policy.backoff(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+fn is_transient(error: &ProviderError) -> bool {
+    if let Some(response) = error.as_error_response() {
+        return response.code == TOO_MANY_REQUESTS || response.code == LIMIT_EXCEEDED;
+    }
+
+    match error {
+        ProviderError::HTTPError(error) => error
+            .status()
+            .is_some_and(|status| i64::from(status.as_u16()) == TOO_MANY_REQUESTS),
+        // ethers' Http drops the status code and reports a 429 with a
+        // non-JSON body as a deserialization error carrying that body.
+        ProviderError::JsonRpcClientError(error) if error.as_serde_error().is_some() => {
+            is_rate_limited(error.to_string().as_str())
+        }
+        _ => false,
+    }
+}
+
+fn is_rate_limited(response: &str) -> bool {
+    let response = response.to_lowercase();
+
+    ["too many requests", "rate limit", "rate-limit"]
+        .iter()
+        .any(|marker| response.contains(marker))
+}
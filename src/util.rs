@@ -1,4 +1,7 @@
 use base64::Engine;
+use ethers::utils::keccak256;
+use secp256k1::PublicKey;
+use serde_json::Value;
 
 pub fn strip0x(value: String) -> String {
     if value.starts_with("0x") {
@@ -19,11 +22,11 @@ pub fn remove_zero_bytes(value: String) -> Result<String, std::string::FromUtf8E
     )
 }
 
-pub fn encode_base64(value: String) -> String {
+pub fn encode_base64(value: &[u8]) -> String {
     base64::engine::general_purpose::STANDARD.encode(value)
 }
 
-pub fn encode_base58(value: String) -> String {
+pub fn encode_base58(value: &[u8]) -> String {
     bs58::encode(value).into_string()
 }
 
@@ -43,3 +46,77 @@ pub fn get_public_key(identifier: String) -> Option<String> {
 
     None
 }
+
+pub fn public_key_to_address(public_key: &PublicKey) -> String {
+    let hashed = keccak256(&public_key.serialize_uncompressed()[1..]);
+    format!("0x{}", hex::encode(&hashed[12..]))
+}
+
+pub fn parse_public_key(value: &[u8]) -> Option<PublicKey> {
+    PublicKey::from_slice(value).ok()
+}
+
+pub fn is_valid_public_key(value: &[u8]) -> bool {
+    parse_public_key(value).is_some()
+}
+
+pub fn compress_public_key(value: &[u8]) -> Option<[u8; 33]> {
+    parse_public_key(value).map(|key| key.serialize())
+}
+
+pub fn decompress_public_key(value: &[u8]) -> Option<[u8; 65]> {
+    parse_public_key(value).map(|key| key.serialize_uncompressed())
+}
+
+pub fn public_key_hex_to_address(value: &str) -> Option<String> {
+    match hex::decode(strip0x(String::from(value))) {
+        Ok(val) => parse_public_key(&val).map(|key| public_key_to_address(&key)),
+        Err(_error) => None,
+    }
+}
+
+pub fn is_uri(value: &str) -> bool {
+    match value.split_once(':') {
+        Some((scheme, rest)) => {
+            scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || "+-.".contains(character))
+                && !rest.is_empty()
+                && !value.chars().any(|character| character.is_whitespace())
+        }
+        None => false,
+    }
+}
+
+/// A non-empty URI fragment as defined by RFC 3986: pchars, `/` and `?`,
+/// with `%` only as part of a percent-encoded octet.
+pub fn is_uri_fragment(value: &str) -> bool {
+    let bytes = value.as_bytes();
+
+    !bytes.is_empty()
+        && bytes.iter().enumerate().all(|(index, byte)| match byte {
+            b'%' => {
+                bytes.len() > index + 2
+                    && bytes[index + 1].is_ascii_hexdigit()
+                    && bytes[index + 2].is_ascii_hexdigit()
+            }
+            _ => byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@/?".contains(byte),
+        })
+}
+
+pub fn is_valid_service_endpoint(value: &Value) -> bool {
+    match value {
+        Value::String(val) => is_uri(val),
+        Value::Object(val) => !val.is_empty(),
+        Value::Array(val) => {
+            !val.is_empty()
+                && val.iter().all(|item| match item {
+                    Value::String(item) => is_uri(item),
+                    Value::Object(item) => !item.is_empty(),
+                    _ => false,
+                })
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,19 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = resolve)]
+pub async fn resolve_js(did: String, provider: String, accept: String) -> Result<JsValue, JsValue> {
+    let did_doc =
+        match crate::resolve_with_metadata(did.as_str(), provider.as_str(), accept.as_str())
+            .await
+            .and_then(|val| val.document())
+        {
+            Ok(val) => val,
+            Err(error) => return Err(JsValue::from_str(error.to_string().as_str())),
+        };
+
+    match did_doc.serialize(&serde_wasm_bindgen::Serializer::json_compatible()) {
+        Ok(val) => Ok(val),
+        Err(error) => Err(JsValue::from_str(error.to_string().as_str())),
+    }
+}
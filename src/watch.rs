@@ -0,0 +1,154 @@
+use ethers::providers::{Middleware, Provider, Ws};
+use ethers::types::{Filter, Log, H160, U64};
+use fi_common::error::Error;
+use futures::{Stream, StreamExt};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+use crate::events::attribute_changed::DID_ATTRIBUTE_CHANGED_TOPIC;
+use crate::events::delegate_changed::DID_DELEGATE_CHANGED_TOPIC;
+use crate::events::owner_changed::DID_OWNER_CHANGED_TOPIC;
+use crate::history::RegistryEvent;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+pub fn watch_registry(
+    provider_url: &str,
+    contract_address: H160,
+    identity: Option<H160>,
+) -> Result<impl Stream<Item = Result<RegistryEvent, Error>>, Error> {
+    let runtime = match tokio::runtime::Handle::try_current() {
+        Ok(val) => val,
+        Err(_error) => {
+            return Err(Error::new(
+                "Registry events can only be watched from within a Tokio runtime",
+            ))
+        }
+    };
+
+    let (sender, receiver) = unbounded_channel();
+
+    let event_topics = [
+        DID_ATTRIBUTE_CHANGED_TOPIC,
+        DID_DELEGATE_CHANGED_TOPIC,
+        DID_OWNER_CHANGED_TOPIC,
+    ];
+
+    let mut filter = Filter::new()
+        .address(ethers::types::ValueOrArray::Value(contract_address))
+        .events(event_topics);
+
+    if let Some(identity) = identity {
+        filter = filter.topic1(identity);
+    }
+
+    let provider_url = String::from(provider_url);
+
+    runtime.spawn(async move {
+        let mut last_seen: Option<(u64, u64)> = None;
+
+        // Dropping the stream closes the channel, which ends the task and its
+        // connection even if the registry stays quiet.
+        loop {
+            tokio::select! {
+                _ = sender.closed() => return,
+                result = subscribe(provider_url.as_str(), &filter, &mut last_seen, &sender) => {
+                    if let Err(error) = result {
+                        if sender.send(Err(error)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = sender.closed() => return,
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
+            }
+        }
+    });
+
+    Ok(futures::stream::unfold(
+        receiver,
+        |mut receiver| async move { receiver.recv().await.map(|val| (val, receiver)) },
+    ))
+}
+
+async fn subscribe(
+    provider_url: &str,
+    filter: &Filter,
+    last_seen: &mut Option<(u64, u64)>,
+    sender: &UnboundedSender<Result<RegistryEvent, Error>>,
+) -> Result<(), Error> {
+    // Reconnects are handled here so that every new connection back-fills
+    // the logs emitted while the previous one was down.
+    let provider = match Provider::<Ws>::connect_with_reconnects(provider_url, 0).await {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    let mut stream = match provider.subscribe_logs(filter).await {
+        Ok(val) => val,
+        Err(error) => return Err(Error::new(error.to_string().as_str())),
+    };
+
+    if let Some((block_number, _log_index)) = *last_seen {
+        let latest = match provider.get_block_number().await {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        let backfill = filter
+            .clone()
+            .from_block(U64::from(block_number))
+            .to_block(latest);
+
+        let logs = match provider.get_logs(&backfill).await {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        for log in logs {
+            if !forward(&provider, log, last_seen, sender).await {
+                return Ok(());
+            }
+        }
+    }
+
+    while let Some(log) = stream.next().await {
+        if !forward(&provider, log, last_seen, sender).await {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+async fn forward(
+    provider: &Provider<Ws>,
+    log: Log,
+    last_seen: &mut Option<(u64, u64)>,
+    sender: &UnboundedSender<Result<RegistryEvent, Error>>,
+) -> bool {
+    if log.removed.is_some_and(|removed| removed) {
+        return true;
+    }
+
+    let position = match (log.block_number, log.log_index) {
+        (Some(block_number), Some(log_index)) => (block_number.as_u64(), log_index.as_u64()),
+        _ => return true,
+    };
+
+    if last_seen.is_some_and(|seen| position <= seen) {
+        return true;
+    }
+
+    let timestamp = match provider.get_block(position.0).await {
+        Ok(val) => val.map(|block| block.timestamp.as_u64()),
+        Err(_error) => None,
+    };
+
+    *last_seen = Some(position);
+
+    sender.send(RegistryEvent::from_log(log, timestamp)).is_ok()
+}
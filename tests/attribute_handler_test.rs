@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use common::attribute_changed;
+use ethers::types::{Address, Log};
+use fi_common::{did::Service, error::Error};
+use fi_ethr_resolver::{
+    resolve_from_logs, AttributeChange, AttributeHandler, AttributeHandlers, DidDoc, OutputProfile,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+struct ProfileHandler;
+
+impl AttributeHandler for ProfileHandler {
+    fn apply(&self, change: &AttributeChange, did_doc: &mut DidDoc) -> Result<(), Error> {
+        let payload = match serde_json::from_slice::<Value>(&change.value) {
+            Ok(val) => val,
+            Err(error) => return Err(Error::new(error.to_string().as_str())),
+        };
+
+        let service = Service {
+            id: format!("{}#profile", did_doc.doc.id),
+            _type: String::from("Profile"),
+            service_endpoint: payload["url"].clone(),
+        };
+
+        did_doc.services.insert(change.event_index.clone(), service);
+        Ok(())
+    }
+}
+
+fn identity() -> Address {
+    Address::repeat_byte(0x33)
+}
+
+fn attribute(name: &str, value: &str) -> Log {
+    attribute_changed(identity(), name, value.as_bytes(), 0, 1)
+}
+
+#[test]
+pub fn registered_handler_receives_matching_attributes() {
+    let did = format!("did:ethr:{:#x}", identity());
+
+    let mut attribute_handlers = AttributeHandlers::new();
+    attribute_handlers
+        .register("^did/svc/Profile$", Arc::new(ProfileHandler))
+        .unwrap();
+
+    let profile = OutputProfile {
+        attribute_handlers,
+        ..OutputProfile::default()
+    };
+
+    let logs = vec![attribute(
+        "did/svc/Profile",
+        r#"{"url":"https://profile.example.com"}"#,
+    )];
+
+    let result = match resolve_from_logs(&did, "application/did+json", 1, None, logs, &profile) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    assert_eq!(
+        result.document().unwrap()["service"],
+        json!([{
+            "id": format!("{}#profile", did),
+            "type": "Profile",
+            "serviceEndpoint": "https://profile.example.com"
+        }])
+    );
+}
@@ -0,0 +1,84 @@
+use fi_ethr_resolver::{ethr_to_pkh, pkh_document, pkh_to_ethr, AccountId, ChainId};
+use serde_json::json;
+
+const ADDRESS: &str = "0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+#[test]
+pub fn caip_identifiers_round_trip() {
+    let chain_id = ChainId::parse("eip155:11155111").unwrap();
+    assert_eq!(chain_id, ChainId::eip155(11155111));
+    assert_eq!(chain_id.eip155_chain_id(), Some(11155111));
+    assert_eq!(chain_id.to_string(), "eip155:11155111");
+
+    let account_id = AccountId::parse(format!("eip155:1:{}", ADDRESS).as_str()).unwrap();
+    assert_eq!(account_id, AccountId::eip155(1, ADDRESS));
+    assert_eq!(account_id.to_string(), format!("eip155:1:{}", ADDRESS));
+
+    assert_eq!(
+        AccountId::parse(
+            "bip122:000000000019d6689c085ae165831e93:128Lkh3S7CkDTBZ8W7BbpsN3YYizJMp8p6"
+        )
+        .unwrap()
+        .chain_id
+        .eip155_chain_id(),
+        None
+    );
+
+    assert!(ChainId::parse("eip155").is_err());
+    assert!(AccountId::parse("eip155:1:0x1234").is_err());
+    assert!(AccountId::parse("eip155::0xb9c5714089478a327f09197987f16f9e5d936e8a").is_err());
+}
+
+#[test]
+pub fn ethr_and_pkh_dids_convert() {
+    let mainnet = format!("did:pkh:eip155:1:{}", ADDRESS);
+    let sepolia = format!("did:pkh:eip155:11155111:{}", ADDRESS);
+
+    assert_eq!(
+        ethr_to_pkh(&format!("did:ethr:{}", ADDRESS), None).unwrap(),
+        mainnet
+    );
+    assert_eq!(
+        ethr_to_pkh(&format!("did:ethr:0xaa36a7:{}", ADDRESS), None).unwrap(),
+        sepolia
+    );
+    assert_eq!(
+        ethr_to_pkh(&format!("did:ethr:sepolia:{}", ADDRESS), Some(11155111)).unwrap(),
+        sepolia
+    );
+    assert_eq!(
+        ethr_to_pkh(&format!("did:ethr:sepolia:{}", ADDRESS), None).unwrap(),
+        sepolia
+    );
+    assert_eq!(
+        ethr_to_pkh(&format!("did:ethr:dev:{}", ADDRESS), None)
+            .err()
+            .unwrap()
+            .to_string(),
+        "The chain id of network dev is unknown"
+    );
+
+    assert_eq!(
+        pkh_to_ethr(&mainnet).unwrap(),
+        format!("did:ethr:{}", ADDRESS)
+    );
+    assert_eq!(
+        pkh_to_ethr(&sepolia).unwrap(),
+        format!("did:ethr:0xaa36a7:{}", ADDRESS)
+    );
+}
+
+#[test]
+pub fn pkh_document_uses_blockchain_account_id() {
+    let did = format!("did:pkh:eip155:1:{}", ADDRESS);
+    let document = serde_json::to_value(pkh_document(&did).unwrap()).unwrap();
+
+    assert_eq!(
+        document["verificationMethod"][0]["blockchainAccountId"],
+        json!(format!("eip155:1:{}", ADDRESS))
+    );
+    assert_eq!(
+        document["authentication"],
+        json!([format!("{}#blockchainAccountId", did)])
+    );
+}
@@ -0,0 +1,149 @@
+use common::{delegate_changed, owner_changed, with_log_index, Chain};
+use ethers::abi::Contract;
+use ethers::contract::EthEvent;
+use ethers::types::{Address, Log};
+use fi_ethr_resolver::{
+    verify_change_chain, DIDAttributeChanged, DIDDelegateChanged, DIDOwnerChanged, EthrResolver,
+    NetworkConfig, RegistryEvent, DID_JSON,
+};
+
+mod common;
+
+fn identity() -> Address {
+    Address::repeat_byte(0x11)
+}
+
+fn delegate() -> Address {
+    Address::repeat_byte(0x33)
+}
+
+fn owner_change(block: u64, log_index: u64, previous_change: u64) -> Log {
+    with_log_index(
+        owner_changed(
+            identity(),
+            Address::repeat_byte(0x44),
+            previous_change,
+            block,
+        ),
+        log_index,
+    )
+}
+
+fn delegate_change(block: u64, log_index: u64, previous_change: u64) -> Log {
+    with_log_index(
+        delegate_changed(
+            identity(),
+            "veriKey",
+            delegate(),
+            u64::MAX,
+            previous_change,
+            block,
+        ),
+        log_index,
+    )
+}
+
+fn chain_error(block: u64, logs: &mut [Log]) -> String {
+    verify_change_chain(identity(), block, logs)
+        .err()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+pub fn event_signatures_match_the_registry_abi() {
+    let abi = Contract::load(include_str!("../src/contract-abi.json").as_bytes()).unwrap();
+
+    assert_eq!(
+        abi.event("DIDOwnerChanged").unwrap().signature(),
+        DIDOwnerChanged::signature()
+    );
+    assert_eq!(
+        abi.event("DIDDelegateChanged").unwrap().signature(),
+        DIDDelegateChanged::signature()
+    );
+    assert_eq!(
+        abi.event("DIDAttributeChanged").unwrap().signature(),
+        DIDAttributeChanged::signature()
+    );
+}
+
+#[test]
+pub fn change_chain_links_events_within_a_block() {
+    let mut logs = vec![delegate_change(20, 3, 20), owner_change(20, 1, 10)];
+    assert_eq!(verify_change_chain(identity(), 20, &mut logs).unwrap(), 10);
+    assert_eq!(logs[0].log_index, Some(1u64.into()));
+
+    let mut logs = vec![delegate_change(10, 0, 0)];
+    assert_eq!(verify_change_chain(identity(), 10, &mut logs).unwrap(), 0);
+}
+
+#[test]
+pub fn broken_change_chains_are_rejected() {
+    let prefix = format!("The registry event chain for {:#x} is broken: ", identity());
+
+    assert_eq!(
+        chain_error(20, &mut []),
+        format!("{}no events were returned for block 20", prefix)
+    );
+    assert_eq!(
+        chain_error(20, &mut [owner_change(19, 0, 10)]),
+        format!(
+            "{}an event from another block was returned for block 20",
+            prefix
+        )
+    );
+    assert_eq!(
+        chain_error(20, &mut [owner_change(20, 0, 30)]),
+        format!(
+            "{}the first event returned for block 20 points to block 30",
+            prefix
+        )
+    );
+    assert_eq!(
+        chain_error(
+            20,
+            &mut [owner_change(20, 0, 10), delegate_change(20, 1, 15)]
+        ),
+        format!("{}an event in block 20 points to block 15", prefix)
+    );
+
+    let mut undecodable = owner_change(20, 0, 10);
+    undecodable.data = vec![0u8; 3].into();
+    assert_eq!(
+        chain_error(20, &mut [undecodable]),
+        format!("{}an event in block 20 could not be decoded", prefix)
+    );
+}
+
+#[tokio::test]
+pub async fn registry_walk_follows_delegate_changes() {
+    let mut chain = Chain::new(1337, 100);
+    chain.changed.insert(identity(), 20);
+    chain.logs = vec![
+        owner_change(10, 0, 0),
+        delegate_change(20, 0, 10),
+        delegate_change(20, 1, 20),
+    ];
+    let client = chain.into_mock();
+
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("dev", "http://127.0.0.1:8545"))
+        .build_with(move |_network| Ok(client.clone()))
+        .unwrap();
+    let did = format!("did:ethr:dev:{:#x}", identity());
+
+    let history = resolver.history(&did).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(matches!(
+        &history[1],
+        RegistryEvent::DelegateChanged { delegate_type, delegate: address, previous_change: 10, .. }
+            if delegate_type == "veriKey" && address == &format!("{:#x}", delegate())
+    ));
+
+    let document = resolver.resolve(&did, DID_JSON).await.unwrap();
+    assert_eq!(
+        document["verificationMethod"][1]["blockchainAccountId"],
+        format!("eip155:1337:{:#x}", delegate())
+    );
+}
@@ -0,0 +1,515 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers::abi::{encode, Token};
+use ethers::contract::EthEvent;
+use ethers::providers::{JsonRpcClient, JsonRpcError, MockError};
+use ethers::types::{Address, Block, Log, H256, U256, U64};
+use ethers::utils::id;
+use fi_ethr_resolver::{DIDAttributeChanged, DIDDelegateChanged, DIDOwnerChanged};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+pub const REGISTRY: &str = "0xdca7ef03e98e0dc2b855be647c39abe984fcf21b";
+
+pub fn registry() -> Address {
+    REGISTRY.parse().unwrap()
+}
+
+pub fn bytes32(value: &str) -> Token {
+    let mut bytes = [0u8; 32];
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+    Token::FixedBytes(bytes.to_vec())
+}
+
+pub fn registry_log(signature: H256, identity: Address, data: &[Token], block: u64) -> Log {
+    Log {
+        address: registry(),
+        topics: vec![signature, H256::from(identity)],
+        data: encode(data).into(),
+        block_number: Some(block.into()),
+        log_index: Some(0u64.into()),
+        transaction_hash: Some(H256::from_low_u64_be(block)),
+        ..Default::default()
+    }
+}
+
+pub fn owner_changed(identity: Address, owner: Address, previous_change: u64, block: u64) -> Log {
+    registry_log(
+        DIDOwnerChanged::signature(),
+        identity,
+        &[Token::Address(owner), Token::Uint(previous_change.into())],
+        block,
+    )
+}
+
+pub fn delegate_changed(
+    identity: Address,
+    delegate_type: &str,
+    delegate: Address,
+    valid_to: u64,
+    previous_change: u64,
+    block: u64,
+) -> Log {
+    registry_log(
+        DIDDelegateChanged::signature(),
+        identity,
+        &[
+            bytes32(delegate_type),
+            Token::Address(delegate),
+            Token::Uint(valid_to.into()),
+            Token::Uint(previous_change.into()),
+        ],
+        block,
+    )
+}
+
+pub fn attribute_changed(
+    identity: Address,
+    name: &str,
+    value: &[u8],
+    previous_change: u64,
+    block: u64,
+) -> Log {
+    registry_log(
+        DIDAttributeChanged::signature(),
+        identity,
+        &[
+            bytes32(name),
+            Token::Bytes(value.to_vec()),
+            Token::Uint(u64::MAX.into()),
+            Token::Uint(previous_change.into()),
+        ],
+        block,
+    )
+}
+
+pub fn with_log_index(mut log: Log, log_index: u64) -> Log {
+    log.log_index = Some(log_index.into());
+    log
+}
+
+type Handler = dyn Fn(&str, &Value) -> Option<Value> + Send + Sync;
+
+#[derive(Clone)]
+pub struct RpcMock {
+    handler: Arc<Handler>,
+    calls: Arc<Mutex<Vec<(String, Value)>>>,
+    delay: Duration,
+}
+
+impl RpcMock {
+    pub fn new<F>(handler: F) -> RpcMock
+    where
+        F: Fn(&str, &Value) -> Option<Value> + Send + Sync + 'static,
+    {
+        RpcMock {
+            handler: Arc::new(handler),
+            calls: Arc::new(Mutex::new(Vec::new())),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> RpcMock {
+        self.delay = delay;
+        self
+    }
+
+    pub fn calls(&self, method: &str) -> usize {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _params)| name == method)
+            .count()
+    }
+
+    pub fn params(&self, method: &str) -> Vec<Value> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _params)| name == method)
+            .map(|(_name, params)| params.clone())
+            .collect()
+    }
+
+    pub fn total_calls(&self) -> usize {
+        self.calls.lock().unwrap().len()
+    }
+}
+
+impl Debug for RpcMock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcMock").finish()
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcMock {
+    type Error = MockError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, MockError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)?;
+        self.calls
+            .lock()
+            .unwrap()
+            .push((String::from(method), params.clone()));
+
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        match (self.handler)(method, &params) {
+            Some(val) => Ok(serde_json::from_value(val)?),
+            None => Err(MockError::JsonRpcError(JsonRpcError {
+                code: -32601,
+                message: format!("Unexpected request: {} {}", method, params),
+                data: None,
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Chain {
+    pub chain_id: u64,
+    pub latest: u64,
+    pub timestamps: HashMap<u64, u64>,
+    pub changed: HashMap<Address, u64>,
+    pub owners: HashMap<Address, Address>,
+    pub valid_delegates: HashSet<(Address, String, Address)>,
+    pub logs: Vec<Log>,
+}
+
+impl Chain {
+    pub fn new(chain_id: u64, latest: u64) -> Chain {
+        Chain {
+            chain_id,
+            latest,
+            ..Default::default()
+        }
+    }
+
+    pub fn into_mock(self) -> RpcMock {
+        RpcMock::new(move |method, params| self.respond(method, params))
+    }
+
+    pub fn respond(&self, method: &str, params: &Value) -> Option<Value> {
+        match method {
+            "eth_chainId" => Some(Value::from(format!("{:#x}", self.chain_id))),
+            "eth_blockNumber" => Some(Value::from(format!("{:#x}", self.latest))),
+            "eth_getBlockByNumber" => {
+                let number = match params[0].as_str()? {
+                    "latest" => self.latest,
+                    val => quantity(val)?,
+                };
+                Some(self.block(number))
+            }
+            "eth_call" => self.call(&params[0]),
+            "eth_getLogs" => Some(self.get_logs(&params[0])),
+            _ => None,
+        }
+    }
+
+    pub fn block(&self, number: u64) -> Value {
+        serde_json::to_value(Block::<H256> {
+            hash: Some(H256::from_low_u64_be(number)),
+            number: Some(U64::from(number)),
+            timestamp: U256::from(self.timestamps.get(&number).copied().unwrap_or(number)),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn call(&self, transaction: &Value) -> Option<Value> {
+        let data = transaction["data"]
+            .as_str()
+            .or(transaction["input"].as_str())?;
+        let data = hex::decode(data.trim_start_matches("0x")).ok()?;
+        let identity = Address::from_slice(&data[16..36]);
+
+        let result = match &data[..4] {
+            selector if selector == &id("changed(address)")[..] => encode(&[Token::Uint(
+                self.changed.get(&identity).copied().unwrap_or(0).into(),
+            )]),
+            selector if selector == &id("identityOwner(address)")[..] => encode(&[Token::Address(
+                self.owners.get(&identity).copied().unwrap_or(identity),
+            )]),
+            selector if selector == &id("validDelegate(address,bytes32,address)")[..] => {
+                let delegate_type = String::from_utf8_lossy(&data[36..68])
+                    .trim_end_matches('\0')
+                    .to_string();
+                let delegate = Address::from_slice(&data[80..100]);
+                encode(&[Token::Bool(self.valid_delegates.contains(&(
+                    identity,
+                    delegate_type,
+                    delegate,
+                )))])
+            }
+            _ => return None,
+        };
+
+        Some(Value::from(format!("0x{}", hex::encode(result))))
+    }
+
+    fn get_logs(&self, filter: &Value) -> Value {
+        let from_block = filter["fromBlock"].as_str().and_then(quantity).unwrap_or(0);
+        let to_block = filter["toBlock"]
+            .as_str()
+            .and_then(quantity)
+            .unwrap_or(self.latest);
+
+        let topic0 = match &filter["topics"][0] {
+            Value::Array(topics) => topics
+                .iter()
+                .filter_map(|topic| topic.as_str())
+                .map(String::from)
+                .collect::<Vec<String>>(),
+            Value::String(topic) => vec![topic.clone()],
+            _ => Vec::new(),
+        };
+        let topic1 = filter["topics"][1].as_str();
+
+        let logs = self
+            .logs
+            .iter()
+            .filter(|log| {
+                let block = log.block_number.map_or(0, |val| val.as_u64());
+                block >= from_block
+                    && block <= to_block
+                    && (topic0.is_empty() || topic0.contains(&format!("{:#x}", log.topics[0])))
+                    && topic1.is_none_or(|topic| {
+                        log.topics
+                            .get(1)
+                            .is_some_and(|val| format!("{:#x}", val) == topic)
+                    })
+            })
+            .cloned()
+            .collect::<Vec<Log>>();
+
+        serde_json::to_value(logs).unwrap()
+    }
+}
+
+fn quantity(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+pub async fn serve(mock: RpcMock) -> String {
+    serve_throttled(mock, 0).await
+}
+
+/// Serves the mock over HTTP, answering the first `throttled` requests with a
+/// plain-text `429 Too Many Requests` the way rate-limiting proxies do.
+pub async fn serve_throttled(mock: RpcMock, throttled: usize) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let throttled = Arc::new(AtomicUsize::new(throttled));
+
+    tokio::spawn(async move {
+        while let Ok((stream, _address)) = listener.accept().await {
+            tokio::spawn(handle_connection(stream, mock.clone(), throttled.clone()));
+        }
+    });
+
+    url
+}
+
+async fn handle_connection(
+    mut stream: tokio::net::TcpStream,
+    mock: RpcMock,
+    throttled: Arc<AtomicUsize>,
+) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut buffer = Vec::new();
+
+    loop {
+        let header_end = loop {
+            if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                break position + 4;
+            }
+
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            }
+        };
+
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|val| val.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        while buffer.len() < header_end + content_length {
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            }
+        }
+
+        let request: Value =
+            serde_json::from_slice(&buffer[header_end..header_end + content_length]).unwrap();
+        buffer.drain(..header_end + content_length);
+
+        let throttle = throttled
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |val| val.checked_sub(1))
+            .is_ok();
+        if throttle {
+            let body = "Too Many Requests";
+            let response = format!(
+                "HTTP/1.1 429 Too Many Requests\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if stream.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let result = mock
+            .request::<_, Value>(
+                request["method"].as_str().unwrap_or_default(),
+                request["params"].clone(),
+            )
+            .await;
+        let body = match result {
+            Ok(val) => serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": val}),
+            Err(_error) => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32601, "message": "Unexpected request"}
+            }),
+        }
+        .to_string();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Serves the mock over WebSocket. Connection `n` is sent `pushes[n]` as
+/// `eth_subscription` notifications once it subscribes, and every connection
+/// but the last is closed after its pushes. The counter tracks how many
+/// connections are still open.
+pub async fn serve_ws(mock: RpcMock, pushes: Vec<Vec<Log>>) -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let open = Arc::new(AtomicUsize::new(0));
+    let counter = open.clone();
+
+    tokio::spawn(async move {
+        let mut connection = 0;
+
+        while let Ok((stream, _address)) = listener.accept().await {
+            let logs = pushes.get(connection).cloned().unwrap_or_default();
+            let close = connection + 1 < pushes.len();
+            connection += 1;
+
+            let mock = mock.clone();
+            let counter = counter.clone();
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                handle_ws_connection(stream, mock, logs, close).await;
+                counter.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    (url, open)
+}
+
+async fn handle_ws_connection(
+    stream: tokio::net::TcpStream,
+    mock: RpcMock,
+    logs: Vec<Log>,
+    close: bool,
+) {
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(val) => val,
+        Err(_error) => return,
+    };
+
+    while let Some(Ok(message)) = socket.next().await {
+        let request: Value = match message {
+            Message::Text(val) => serde_json::from_str(&val).unwrap(),
+            Message::Close(_frame) => return,
+            _ => continue,
+        };
+
+        let method = request["method"].as_str().unwrap_or_default();
+
+        let body = match method {
+            "eth_subscribe" => {
+                serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+            }
+            _ => match mock
+                .request::<_, Value>(method, request["params"].clone())
+                .await
+            {
+                Ok(val) => {
+                    serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": val})
+                }
+                Err(_error) => serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": {"code": -32601, "message": "Unexpected request"}
+                }),
+            },
+        };
+
+        if socket.send(Message::Text(body.to_string())).await.is_err() {
+            return;
+        }
+
+        if method != "eth_subscribe" {
+            continue;
+        }
+
+        for log in &logs {
+            let notification = serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_subscription",
+                "params": {"subscription": "0x1", "result": log}
+            });
+
+            if socket
+                .send(Message::Text(notification.to_string()))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+
+        if close {
+            let _ = socket.close(None).await;
+            return;
+        }
+    }
+}
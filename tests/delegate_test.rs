@@ -0,0 +1,73 @@
+use common::delegate_changed;
+use ethers::types::{Address, Log};
+use fi_ethr_resolver::{resolve_from_logs, OutputProfile};
+use serde_json::{json, Value};
+
+mod common;
+
+fn identity() -> Address {
+    Address::repeat_byte(0x22)
+}
+
+fn delegate() -> Address {
+    Address::repeat_byte(0x33)
+}
+
+fn did() -> String {
+    format!("did:ethr:{:#x}", identity())
+}
+
+fn resolve_document(now: Option<u64>, logs: Vec<Log>) -> Value {
+    match resolve_from_logs(
+        &did(),
+        "application/did+json",
+        1,
+        now,
+        logs,
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val.document().unwrap(),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+#[test]
+pub fn revoked_delegate_is_removed_offline() {
+    let document = resolve_document(
+        Some(1_000),
+        vec![
+            delegate_changed(identity(), "veriKey", delegate(), 2_000, 0, 10),
+            delegate_changed(identity(), "veriKey", delegate(), 0, 10, 12),
+        ],
+    );
+
+    assert_eq!(document["verificationMethod"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        document["assertionMethod"],
+        json!([format!("{}#controller", did())])
+    );
+}
+
+#[test]
+pub fn delegate_expiry_follows_the_clock() {
+    let logs = vec![delegate_changed(
+        identity(),
+        "veriKey",
+        delegate(),
+        2_000,
+        0,
+        10,
+    )];
+
+    assert_eq!(
+        resolve_document(Some(1_000), logs.clone())["verificationMethod"][1]["id"],
+        json!(format!("{}#delegate-1", did()))
+    );
+    assert_eq!(
+        resolve_document(Some(2_000), logs)["verificationMethod"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
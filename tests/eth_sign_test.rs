@@ -0,0 +1,134 @@
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::utils::{hash_message, keccak256};
+use fi_common::did::DidDocument;
+use fi_ethr_resolver::{
+    resolve_from_logs, verify_personal_message, verify_typed_data, OutputProfile, DID_JSON,
+};
+use secp256k1::{Message, Secp256k1, SecretKey};
+use serde_json::json;
+
+// The signer of the EIP-712 specification example, keccak256("cow").
+const MAIL_SIGNER: &str = "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826";
+
+fn document(address: &str) -> DidDocument {
+    let did = format!("did:ethr:{}", address);
+
+    match resolve_from_logs(
+        &did,
+        DID_JSON,
+        1,
+        None,
+        Vec::new(),
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val.did_document,
+        Err(error) => panic!("{}", error),
+    }
+}
+
+fn mail() -> TypedData {
+    serde_json::from_value(json!({
+        "types": {
+            "EIP712Domain": [
+                {"name": "name", "type": "string"},
+                {"name": "version", "type": "string"},
+                {"name": "chainId", "type": "uint256"},
+                {"name": "verifyingContract", "type": "address"}
+            ],
+            "Person": [
+                {"name": "name", "type": "string"},
+                {"name": "wallet", "type": "address"}
+            ],
+            "Mail": [
+                {"name": "from", "type": "Person"},
+                {"name": "to", "type": "Person"},
+                {"name": "contents", "type": "string"}
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+            "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+            "contents": "Hello, Bob!"
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+pub fn eip712_mail_example_is_verified() {
+    let typed_data = mail();
+
+    assert_eq!(
+        hex::encode(typed_data.encode_eip712().unwrap()),
+        "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+    );
+
+    let signature = concat!(
+        "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d",
+        "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562",
+        "1c"
+    );
+
+    assert_eq!(
+        verify_typed_data(&document(MAIL_SIGNER), &typed_data, signature).unwrap(),
+        format!("did:ethr:{}#controller", MAIL_SIGNER)
+    );
+
+    let other = "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+    assert_eq!(
+        verify_typed_data(&document(other), &typed_data, signature)
+            .err()
+            .unwrap()
+            .to_string(),
+        format!(
+            "No recovery method of did:ethr:{} matches the signature",
+            other
+        )
+    );
+}
+
+#[test]
+pub fn eip191_personal_message_is_verified() {
+    assert_eq!(
+        hex::encode(hash_message("Hello World")),
+        "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2"
+    );
+
+    let secret_key = SecretKey::from_slice(&keccak256("cow")).unwrap();
+    let (recovery_id, signature) = Secp256k1::new()
+        .sign_ecdsa_recoverable(
+            &Message::from_digest(hash_message("Hello World").0),
+            &secret_key,
+        )
+        .serialize_compact();
+
+    let mut bytes = signature.to_vec();
+    bytes.push(27 + recovery_id.to_i32() as u8);
+    let signature = format!("0x{}", hex::encode(bytes));
+
+    assert_eq!(
+        verify_personal_message(&document(MAIL_SIGNER), b"Hello World", &signature).unwrap(),
+        format!("did:ethr:{}#controller", MAIL_SIGNER)
+    );
+    assert!(verify_personal_message(&document(MAIL_SIGNER), b"Hello World!", &signature).is_err());
+    assert!(verify_personal_message(
+        &document("0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+        b"Hello World",
+        &signature
+    )
+    .is_err());
+    assert_eq!(
+        verify_personal_message(&document(MAIL_SIGNER), b"Hello World", "0x00")
+            .err()
+            .unwrap()
+            .to_string(),
+        "Signature must be 65 bytes"
+    );
+}
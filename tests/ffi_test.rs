@@ -0,0 +1,275 @@
+#![cfg(feature = "native")]
+
+use common::{attribute_changed, serve, Chain};
+use ethers::types::Address;
+use fi_ethr_resolver::to_cbor;
+use serde_json::Value;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+mod common;
+
+#[repr(C)]
+struct EthrResolverHandle {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    fn ethr_resolver_new() -> *mut EthrResolverHandle;
+    fn ethr_resolver_free(handle: *mut EthrResolverHandle);
+    fn ethr_resolver_resolve(
+        handle: *const EthrResolverHandle,
+        did: *const c_char,
+        rpc_url: *const c_char,
+        accept: *const c_char,
+        out_json: *mut *mut c_char,
+        out_err: *mut *mut c_char,
+    ) -> c_int;
+    fn ethr_resolve(
+        did: *const c_char,
+        rpc_url: *const c_char,
+        accept: *const c_char,
+        out_json: *mut *mut c_char,
+        out_err: *mut *mut c_char,
+    ) -> c_int;
+    fn ethr_resolver_resolve_representation(
+        handle: *const EthrResolverHandle,
+        did: *const c_char,
+        rpc_url: *const c_char,
+        accept: *const c_char,
+        out_bytes: *mut *mut u8,
+        out_len: *mut usize,
+        out_err: *mut *mut c_char,
+    ) -> c_int;
+    fn ethr_string_free(value: *mut c_char);
+    fn ethr_bytes_free(value: *mut u8, len: usize);
+}
+
+const ETHR_OK: c_int = 0;
+const ETHR_INVALID_ARGUMENT: c_int = 2;
+const ETHR_PANIC: c_int = 3;
+
+const DID: &str = "did:ethr:0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+unsafe fn take_string(value: *mut c_char) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+
+    let string = CStr::from_ptr(value).to_string_lossy().to_string();
+    ethr_string_free(value);
+    Some(string)
+}
+
+unsafe fn call(
+    handle: Option<*const EthrResolverHandle>,
+    did: Option<&str>,
+    rpc_url: &str,
+) -> (c_int, Option<String>, Option<String>) {
+    call_with(handle, did, rpc_url, "application/did+json")
+}
+
+unsafe fn call_with(
+    handle: Option<*const EthrResolverHandle>,
+    did: Option<&str>,
+    rpc_url: &str,
+    accept: &str,
+) -> (c_int, Option<String>, Option<String>) {
+    let did = did.map(|val| CString::new(val).unwrap());
+    let rpc_url = CString::new(rpc_url).unwrap();
+    let accept = CString::new(accept).unwrap();
+    let did_ptr = did.as_ref().map_or(ptr::null(), |val| val.as_ptr());
+
+    let mut out_json = ptr::null_mut();
+    let mut out_err = ptr::null_mut();
+
+    let status = match handle {
+        Some(handle) => ethr_resolver_resolve(
+            handle,
+            did_ptr,
+            rpc_url.as_ptr(),
+            accept.as_ptr(),
+            &mut out_json,
+            &mut out_err,
+        ),
+        None => ethr_resolve(
+            did_ptr,
+            rpc_url.as_ptr(),
+            accept.as_ptr(),
+            &mut out_json,
+            &mut out_err,
+        ),
+    };
+
+    (status, take_string(out_json), take_string(out_err))
+}
+
+unsafe fn represent(
+    handle: *const EthrResolverHandle,
+    rpc_url: &str,
+    accept: &str,
+) -> (c_int, Option<Vec<u8>>, Option<String>) {
+    let did = CString::new(DID).unwrap();
+    let rpc_url = CString::new(rpc_url).unwrap();
+    let accept = CString::new(accept).unwrap();
+
+    let mut out_bytes = ptr::null_mut();
+    let mut out_len = 0;
+    let mut out_err = ptr::null_mut();
+
+    let status = ethr_resolver_resolve_representation(
+        handle,
+        did.as_ptr(),
+        rpc_url.as_ptr(),
+        accept.as_ptr(),
+        &mut out_bytes,
+        &mut out_len,
+        &mut out_err,
+    );
+
+    let bytes = match out_bytes.is_null() {
+        true => None,
+        false => {
+            let bytes = std::slice::from_raw_parts(out_bytes, out_len).to_vec();
+            ethr_bytes_free(out_bytes, out_len);
+            Some(bytes)
+        }
+    };
+
+    (status, bytes, take_string(out_err))
+}
+
+fn assert_normalized(value: &Value) {
+    match value {
+        Value::Object(members) => {
+            for (key, val) in members {
+                assert!(!val.is_null(), "{} is null", key);
+                assert_ne!(key, "service_endpoint");
+                assert_normalized(val);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(assert_normalized),
+        _ => {}
+    }
+}
+
+fn chain() -> Chain {
+    let identity = DID.rsplit(':').next().unwrap().parse::<Address>().unwrap();
+
+    let mut chain = Chain::new(1, 100);
+    chain.changed.insert(identity, 10);
+    chain.logs = vec![attribute_changed(
+        identity,
+        "did/svc/HubService",
+        b"https://hubs.example.com",
+        0,
+        10,
+    )];
+    chain
+}
+
+#[test]
+pub fn c_abi_returns_the_normalized_representation() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let provider = runtime.block_on(serve(chain().into_mock()));
+
+    unsafe {
+        let (status, json, error) = call(None, Some(DID), &provider);
+        assert_eq!((status, error), (ETHR_OK, None));
+        let document: Value = serde_json::from_str(&json.unwrap()).unwrap();
+        assert_normalized(&document);
+        assert!(document.get("services").is_none());
+        assert_eq!(
+            document["service"][0]["serviceEndpoint"],
+            "https://hubs.example.com"
+        );
+
+        let (status, json, error) = call_with(None, Some(DID), &provider, "application/did+cbor");
+        assert_eq!((status, json), (ETHR_INVALID_ARGUMENT, None));
+        assert_eq!(
+            error.as_deref(),
+            Some("application/did+cbor is binary, use ethr_resolver_resolve_representation")
+        );
+
+        let handle = ethr_resolver_new();
+
+        let (status, bytes, error) = represent(handle, &provider, "application/did+cbor");
+        assert_eq!((status, error), (ETHR_OK, None));
+        assert_eq!(bytes.unwrap(), to_cbor(&document));
+
+        let (status, bytes, error) = represent(ptr::null(), &provider, "application/did+cbor");
+        assert_eq!((status, bytes), (ETHR_INVALID_ARGUMENT, None));
+        assert!(error.is_some());
+
+        ethr_resolver_free(handle);
+    }
+}
+
+#[test]
+pub fn c_abi_resolves_and_reports_errors() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let provider = runtime.block_on(serve(Chain::new(1, 100).into_mock()));
+
+    unsafe {
+        let (status, json, error) = call(None, Some(DID), &provider);
+        assert_eq!((status, error), (ETHR_OK, None));
+        let document: Value = serde_json::from_str(&json.unwrap()).unwrap();
+        assert_eq!(document["id"], DID);
+
+        let handle = ethr_resolver_new();
+        assert!(!handle.is_null());
+
+        let (status, json, _error) = call(Some(handle), Some(DID), &provider);
+        assert_eq!(status, ETHR_OK);
+        assert!(json.is_some());
+
+        let (status, json, error) = call(Some(handle), None, &provider);
+        assert_eq!(status, ETHR_INVALID_ARGUMENT);
+        assert_eq!(json, None);
+        assert_eq!(
+            error.as_deref(),
+            Some("did, rpc_url and accept must be non-null UTF-8 strings")
+        );
+
+        ethr_resolver_free(handle);
+
+        let (status, _json, error) = call(Some(ptr::null()), Some(DID), &provider);
+        assert_eq!(status, ETHR_INVALID_ARGUMENT);
+        assert_eq!(error.as_deref(), Some("Resolver handle is null"));
+    }
+}
+
+#[test]
+pub fn c_abi_maps_panics_to_an_error_status() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    // Blocking on a new runtime from inside another one panics inside the library.
+    let (status, json, error) =
+        runtime.block_on(async { unsafe { call(None, Some(DID), "http://127.0.0.1:1") } });
+
+    assert_eq!(status, ETHR_PANIC);
+    assert_eq!(json, None);
+    assert_eq!(error.as_deref(), Some("The resolver panicked"));
+}
+
+#[test]
+pub fn c_abi_reports_a_panic_through_the_handle_once() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    unsafe {
+        let handle = ethr_resolver_new();
+
+        // The handle's runtime cannot be blocked on from inside another runtime.
+        let (status, json, error) =
+            runtime.block_on(async { call(Some(handle), Some(DID), "http://127.0.0.1:1") });
+        assert_eq!((status, json), (ETHR_PANIC, None));
+        assert_eq!(error.as_deref(), Some("The resolver panicked"));
+
+        let (status, bytes, error) = runtime
+            .block_on(async { represent(handle, "http://127.0.0.1:1", "application/did+json") });
+        assert_eq!((status, bytes), (ETHR_PANIC, None));
+        assert_eq!(error.as_deref(), Some("The resolver panicked"));
+
+        ethr_resolver_free(handle);
+    }
+}
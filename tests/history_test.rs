@@ -0,0 +1,116 @@
+use common::{
+    attribute_changed, delegate_changed, owner_changed, serve, with_log_index, Chain, REGISTRY,
+};
+use ethers::types::{Address, H256};
+use fi_ethr_resolver::{history, resolve_from_logs, EventMetadata, OutputProfile, RegistryEvent};
+
+mod common;
+
+fn identity() -> Address {
+    Address::repeat_byte(0x11)
+}
+
+fn did() -> String {
+    format!("did:ethr:{:#x}", identity())
+}
+
+fn metadata(block_number: u64, timestamp: u64, log_index: u64) -> EventMetadata {
+    EventMetadata {
+        block_number,
+        timestamp: Some(timestamp),
+        transaction_hash: Some(format!("{:#x}", H256::from_low_u64_be(block_number))),
+        log_index: Some(log_index),
+    }
+}
+
+#[tokio::test]
+pub async fn history_walks_the_registry_in_order() {
+    let owner = Address::repeat_byte(0x22);
+    let delegate = Address::repeat_byte(0x33);
+
+    let mut chain = Chain::new(1, 100);
+    chain.changed.insert(identity(), 20);
+    chain.timestamps.insert(10, 1_000);
+    chain.timestamps.insert(20, 2_000);
+    chain.logs = vec![
+        with_log_index(
+            attribute_changed(
+                identity(),
+                "did/svc/Hub",
+                b"https://hub.example.com",
+                20,
+                20,
+            ),
+            1,
+        ),
+        delegate_changed(identity(), "veriKey", delegate, 5_000, 10, 20),
+        owner_changed(identity(), owner, 0, 10),
+    ];
+
+    let mock = chain.into_mock();
+    let url = serve(mock.clone()).await;
+
+    assert_eq!(
+        history(&did(), &url).await.unwrap(),
+        vec![
+            RegistryEvent::OwnerChanged {
+                metadata: metadata(10, 1_000, 0),
+                identity: format!("{:#x}", identity()),
+                owner: format!("{:#x}", owner),
+                previous_change: 0,
+            },
+            RegistryEvent::DelegateChanged {
+                metadata: metadata(20, 2_000, 0),
+                identity: format!("{:#x}", identity()),
+                delegate_type: String::from("veriKey"),
+                delegate: format!("{:#x}", delegate),
+                valid_to: 5_000u64.into(),
+                previous_change: 10,
+            },
+            RegistryEvent::AttributeChanged {
+                metadata: metadata(20, 2_000, 1),
+                identity: format!("{:#x}", identity()),
+                name: String::from("did/svc/Hub"),
+                value: format!("0x{}", hex::encode(b"https://hub.example.com")),
+                valid_to: u64::MAX.into(),
+                previous_change: 20,
+            },
+        ]
+    );
+
+    let calls = mock.params("eth_call");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0][0]["to"], REGISTRY);
+
+    let filters = mock.params("eth_getLogs");
+    assert_eq!(filters.len(), 2);
+    for filter in filters {
+        assert_eq!(filter[0]["address"], REGISTRY);
+    }
+}
+
+#[tokio::test]
+pub async fn undecodable_registry_logs_are_errors() {
+    let mut malformed = owner_changed(identity(), Address::repeat_byte(0x22), 0, 10);
+    malformed.data = vec![0u8; 7].into();
+
+    assert!(resolve_from_logs(
+        &did(),
+        "application/did+json",
+        1,
+        None,
+        vec![malformed.clone()],
+        &OutputProfile::default(),
+    )
+    .err()
+    .unwrap()
+    .to_string()
+    .starts_with("Could not decode DIDOwnerChanged"));
+
+    let mut chain = Chain::new(1, 100);
+    chain.changed.insert(identity(), 10);
+    chain.logs = vec![malformed];
+
+    let url = serve(chain.into_mock()).await;
+    assert!(history(&did(), &url).await.is_err());
+}
@@ -0,0 +1,311 @@
+use common::{attribute_changed, delegate_changed, owner_changed, with_log_index};
+use ethers::types::Address;
+use fi_ethr_resolver::{AddressLink, AddressRole, OutputProfile, RegistryIndex};
+use serde_json::json;
+
+mod common;
+
+#[test]
+pub fn logs_are_grouped_and_materialized_per_identity() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+    let delegate = Address::repeat_byte(0x33);
+
+    let mut index = RegistryIndex::new(Some("sepolia"), 11155111, OutputProfile::default());
+    let updated = index.ingest(
+        vec![
+            delegate_changed(alice, "veriKey", delegate, u64::MAX, 0, 10),
+            with_log_index(owner_changed(bob, Address::repeat_byte(0x44), 0, 11), 1),
+        ],
+        1_000,
+    );
+
+    let alice_did = format!("did:ethr:sepolia:{:#x}", alice);
+    let bob_did = format!("did:ethr:sepolia:{:#x}", bob);
+
+    assert_eq!(updated, vec![alice_did.clone(), bob_did.clone()]);
+    assert_eq!(index.dids(), vec![alice_did.clone(), bob_did.clone()]);
+    assert_eq!(index.last_block(), Some(11));
+
+    let document =
+        serde_json::to_value(index.document(&alice_did).unwrap().unwrap().did_document).unwrap();
+    assert_eq!(
+        document["verificationMethod"][1]["blockchainAccountId"],
+        json!(format!("eip155:11155111:{:#x}", delegate))
+    );
+
+    assert!(index.document(&format!("did:ethr:{:#x}", alice)).is_none());
+}
+
+#[test]
+pub fn delegates_can_be_queried() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+    let delegate = Address::repeat_byte(0x33);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    index.ingest(
+        vec![
+            delegate_changed(alice, "veriKey", delegate, 2_000, 0, 10),
+            delegate_changed(bob, "veriKey", delegate, 2_000, 0, 10),
+        ],
+        1_000,
+    );
+    index.ingest(
+        vec![delegate_changed(bob, "veriKey", delegate, 0, 0, 12)],
+        1_000,
+    );
+
+    let query = format!("{:#x}", delegate);
+
+    assert_eq!(
+        index.dids_with_delegate(&query, 1_000).unwrap(),
+        vec![format!("did:ethr:{:#x}", alice)]
+    );
+    assert_eq!(
+        index.dids_with_delegate(&query, 3_000).unwrap(),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+pub fn owners_and_delegates_are_found_by_address() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+    let carol = Address::repeat_byte(0x33);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    index.ingest(
+        vec![
+            with_log_index(owner_changed(alice, carol, 0, 10), 1),
+            delegate_changed(bob, "veriKey", carol, 2_000, 0, 10),
+            with_log_index(owner_changed(bob, carol, 0, 11), 1),
+            with_log_index(owner_changed(bob, Address::repeat_byte(0x44), 0, 12), 1),
+        ],
+        1_000,
+    );
+
+    assert_eq!(
+        index.links(&format!("{:#x}", carol), 1_000).unwrap(),
+        vec![
+            AddressLink {
+                did: format!("did:ethr:{:#x}", alice),
+                role: AddressRole::Owner,
+                valid_to: None,
+                valid: true,
+            },
+            AddressLink {
+                did: format!("did:ethr:{:#x}", bob),
+                role: AddressRole::Owner,
+                valid_to: None,
+                valid: false,
+            },
+            AddressLink {
+                did: format!("did:ethr:{:#x}", bob),
+                role: AddressRole::Delegate(String::from("veriKey")),
+                valid_to: Some(2_000u64.into()),
+                valid: true,
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn newest_delegate_change_wins_regardless_of_ingest_order() {
+    let alice = Address::repeat_byte(0x11);
+    let delegate = Address::repeat_byte(0x33);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    index.ingest(
+        vec![delegate_changed(alice, "veriKey", delegate, 0, 10, 12)],
+        1_000,
+    );
+    index.ingest(
+        vec![delegate_changed(alice, "veriKey", delegate, 2_000, 0, 10)],
+        1_000,
+    );
+
+    assert_eq!(
+        index
+            .dids_with_delegate(&format!("{:#x}", delegate), 1_000)
+            .unwrap(),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+pub fn undecodable_logs_are_skipped() {
+    let alice = Address::repeat_byte(0x11);
+
+    let mut broken = delegate_changed(alice, "veriKey", Address::repeat_byte(0x33), 0, 0, 10);
+    broken.data = vec![0u8; 3].into();
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    assert_eq!(
+        index.ingest(
+            vec![
+                broken,
+                attribute_changed(alice, "did/svc/Hub", b"https://hub.example", 0, 11)
+            ],
+            1_000
+        ),
+        vec![format!("did:ethr:{:#x}", alice)]
+    );
+    assert_eq!(index.skipped(), 1);
+    assert!(index
+        .document(&format!("did:ethr:{:#x}", alice))
+        .unwrap()
+        .is_ok());
+}
+
+#[test]
+pub fn documents_indexed_mid_history_are_partial() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    index.ingest(
+        vec![
+            owner_changed(alice, Address::repeat_byte(0x44), 5, 10),
+            owner_changed(bob, Address::repeat_byte(0x44), 0, 10),
+        ],
+        1_000,
+    );
+
+    let partial = index
+        .document(&format!("did:ethr:{:#x}", alice))
+        .unwrap()
+        .unwrap();
+    assert_eq!(partial.did_document_metadata.partial, Some(true));
+    assert_eq!(
+        serde_json::to_value(&partial).unwrap()["didDocumentMetadata"]["partial"],
+        json!(true)
+    );
+
+    let complete = index
+        .document(&format!("did:ethr:{:#x}", bob))
+        .unwrap()
+        .unwrap();
+    assert_eq!(complete.did_document_metadata.partial, None);
+}
+
+#[test]
+pub fn duplicate_and_foreign_logs_are_ignored() {
+    let alice = Address::repeat_byte(0x11);
+    let owner = Address::repeat_byte(0x44);
+
+    let mut foreign = owner_changed(alice, Address::repeat_byte(0x55), 10, 12);
+    foreign.address = Address::repeat_byte(0x66);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    assert_eq!(
+        index.ingest(
+            vec![owner_changed(alice, owner, 0, 10), foreign.clone()],
+            1_000
+        ),
+        vec![format!("did:ethr:{:#x}", alice)]
+    );
+    assert_eq!(
+        index.ingest(vec![owner_changed(alice, owner, 0, 10)], 1_000),
+        Vec::<String>::new()
+    );
+    assert_eq!(index.last_block(), Some(10));
+    assert!(index.links(&format!("{:#x}", owner), 1_000).unwrap()[0].valid);
+
+    index.set_registry(foreign.address);
+    assert_eq!(
+        index.ingest(vec![foreign], 1_000),
+        vec![format!("did:ethr:{:#x}", alice)]
+    );
+    assert!(!index.links(&format!("{:#x}", owner), 1_000).unwrap()[0].valid);
+}
+
+fn method_ids(index: &RegistryIndex, did: &str) -> Vec<String> {
+    let document = index.document(did).unwrap().unwrap().document().unwrap();
+
+    document["verificationMethod"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|method| String::from(method["id"].as_str().unwrap()))
+        .collect()
+}
+
+#[test]
+pub fn revoked_and_expired_delegates_leave_the_document() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+    let delegate = Address::repeat_byte(0x33);
+
+    let alice_did = format!("did:ethr:{:#x}", alice);
+    let bob_did = format!("did:ethr:{:#x}", bob);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    index.ingest(
+        vec![
+            delegate_changed(alice, "veriKey", delegate, 2_000, 0, 10),
+            delegate_changed(bob, "veriKey", delegate, 2_000, 0, 10),
+            delegate_changed(bob, "veriKey", delegate, 0, 10, 12),
+        ],
+        1_000,
+    );
+
+    assert_eq!(
+        method_ids(&index, &alice_did),
+        vec![
+            format!("{}#controller", alice_did),
+            format!("{}#delegate-1", alice_did)
+        ]
+    );
+    assert_eq!(
+        method_ids(&index, &bob_did),
+        vec![format!("{}#controller", bob_did)]
+    );
+    assert_eq!(
+        index
+            .document(&bob_did)
+            .unwrap()
+            .unwrap()
+            .document()
+            .unwrap()["assertionMethod"],
+        json!([format!("{}#controller", bob_did)])
+    );
+
+    assert_eq!(index.ingest(vec![], 3_000), vec![alice_did.clone()]);
+    assert_eq!(
+        method_ids(&index, &alice_did),
+        vec![format!("{}#controller", alice_did)]
+    );
+    assert_eq!(index.ingest(vec![], 4_000), Vec::<String>::new());
+}
+
+#[test]
+pub fn attribute_names_without_a_key_purpose_are_skipped() {
+    let alice = Address::repeat_byte(0x11);
+    let mallory = Address::repeat_byte(0x66);
+
+    let mut index = RegistryIndex::new(None, 1, OutputProfile::default());
+    assert_eq!(
+        index.ingest(
+            vec![
+                attribute_changed(mallory, "did/pub/Secp256k1", b"x", 0, 10),
+                attribute_changed(alice, "did/pub/Ed25519/veriKey", b"0x1234", 0, 11),
+            ],
+            1_000,
+        ),
+        vec![format!("did:ethr:{:#x}", alice)]
+    );
+    assert_eq!(index.skipped(), 1);
+    assert!(index
+        .document(&format!("did:ethr:{:#x}", mallory))
+        .is_none());
+
+    let alice_did = format!("did:ethr:{:#x}", alice);
+    assert_eq!(
+        method_ids(&index, &alice_did),
+        vec![
+            format!("{}#controller", alice_did),
+            format!("{}#delegate-1", alice_did)
+        ]
+    );
+}
@@ -0,0 +1,209 @@
+use fi_ethr_resolver::{
+    canonicalize, compact, expand, load_context, resolve_from_logs, undefined_terms, OutputProfile,
+    DID_LD_JSON,
+};
+use serde_json::{json, Value};
+
+const PUBLIC_KEY_DID: &str =
+    "did:ethr:0x03fdd57adec3d438ea237fe46b33ee1e016eda6b585c3e27ea66686c2ea5358479";
+
+fn resolved_document() -> Value {
+    let result = match resolve_from_logs(
+        PUBLIC_KEY_DID,
+        DID_LD_JSON,
+        1,
+        None,
+        Vec::new(),
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    let mut document = result.document().unwrap();
+    document.as_object_mut().unwrap().remove("service");
+    document
+}
+
+#[test]
+pub fn bundled_contexts_are_loaded_offline() {
+    let document = resolved_document();
+
+    for url in document["@context"].as_array().unwrap() {
+        assert!(load_context(url.as_str().unwrap()).is_some());
+    }
+}
+
+#[test]
+pub fn resolved_document_has_no_undefined_terms() {
+    assert_eq!(
+        undefined_terms(&resolved_document()).unwrap(),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+pub fn resolved_document_round_trips_through_compaction() {
+    let document = resolved_document();
+
+    let expanded = expand(&document).unwrap();
+    let compacted = compact(&expanded, &document["@context"]).unwrap();
+
+    assert_eq!(compacted["id"], document["id"]);
+    assert_eq!(compacted["authentication"], document["authentication"]);
+    assert_eq!(compacted["assertionMethod"], document["assertionMethod"]);
+    assert_eq!(
+        compacted["verificationMethod"][1]["publicKeyHex"],
+        document["verificationMethod"][1]["publicKeyHex"]
+    );
+    assert_eq!(expand(&compacted).unwrap(), expanded);
+}
+
+#[test]
+pub fn unknown_attribute_types_are_reported() {
+    let document = json!({
+        "@context": ["https://www.w3.org/ns/did/v1"],
+        "id": PUBLIC_KEY_DID,
+        "verificationMethod": [{
+            "id": format!("{}#delegate-1", PUBLIC_KEY_DID),
+            "type": "Foo",
+            "controller": PUBLIC_KEY_DID,
+            "publicKeyHex": "00"
+        }]
+    });
+
+    assert_eq!(
+        undefined_terms(&document).unwrap(),
+        vec!["publicKeyHex", "Foo"]
+    );
+}
+
+#[test]
+pub fn expansion_vectors() {
+    let vectors = [
+        (
+            json!({"@context": {"name": "http://schema.org/name"}, "name": "Manu"}),
+            json!([{"http://schema.org/name": [{"@value": "Manu"}]}]),
+        ),
+        (
+            json!({
+                "@context": {"@vocab": "http://example.org/", "knows": {"@type": "@id"}},
+                "@id": "http://example.org/a",
+                "@type": "Person",
+                "knows": "http://example.org/b"
+            }),
+            json!([{
+                "@id": "http://example.org/a",
+                "@type": ["http://example.org/Person"],
+                "http://example.org/knows": [{"@id": "http://example.org/b"}]
+            }]),
+        ),
+        (
+            json!({"@context": {"ex": "http://example.org/"}, "ex:p": {"@value": "v", "@language": "EN"}}),
+            json!([{"http://example.org/p": [{"@value": "v", "@language": "en"}]}]),
+        ),
+        (
+            json!({
+                "@context": {"l": {"@id": "http://example.org/l", "@container": "@list"}},
+                "l": [1, 2]
+            }),
+            json!([{"http://example.org/l": [{"@list": [{"@value": 1}, {"@value": 2}]}]}]),
+        ),
+        (
+            json!({
+                "@context": {"d": {"@id": "http://example.org/d", "@type": "http://www.w3.org/2001/XMLSchema#dateTime"}},
+                "d": "2020-01-01T00:00:00Z"
+            }),
+            json!([{"http://example.org/d": [{
+                "@type": "http://www.w3.org/2001/XMLSchema#dateTime",
+                "@value": "2020-01-01T00:00:00Z"
+            }]}]),
+        ),
+    ];
+
+    for (input, expected) in vectors {
+        assert_eq!(Value::Array(expand(&input).unwrap()), expected, "{}", input);
+    }
+}
+
+#[test]
+pub fn unsupported_keywords_are_rejected() {
+    for keyword in ["@reverse", "@nest", "@included"] {
+        let mut document = json!({"@id": "http://example.org/a"});
+        document[keyword] = json!({"http://example.org/p": "v"});
+
+        assert_eq!(
+            expand(&document).err().unwrap().to_string(),
+            format!("Unsupported JSON-LD keyword: {}", keyword)
+        );
+    }
+}
+
+#[test]
+pub fn canonicalization_vectors() {
+    let vectors = [
+        (
+            json!({
+                "@id": "http://example.org/a",
+                "http://example.org/p": [{"@id": "http://example.org/b"}, "x"]
+            }),
+            concat!(
+                "<http://example.org/a> <http://example.org/p> \"x\" .\n",
+                "<http://example.org/a> <http://example.org/p> <http://example.org/b> .\n",
+            ),
+        ),
+        (
+            json!({
+                "@id": "http://example.org/a",
+                "http://example.org/p": [
+                    {"@value": "a\"b\nc\\d"},
+                    {"@value": "hi", "@language": "en"},
+                    {"@value": "1", "@type": "http://www.w3.org/2001/XMLSchema#integer"}
+                ]
+            }),
+            concat!(
+                "<http://example.org/a> <http://example.org/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+                "<http://example.org/a> <http://example.org/p> \"a\\\"b\\nc\\\\d\" .\n",
+                "<http://example.org/a> <http://example.org/p> \"hi\"@en .\n",
+            ),
+        ),
+        (
+            // First-degree hashes order the inner node ("y") before the outer one.
+            json!({
+                "http://example.org/p": "x",
+                "http://example.org/q": {"http://example.org/p": "y"}
+            }),
+            concat!(
+                "_:c14n0 <http://example.org/p> \"y\" .\n",
+                "_:c14n1 <http://example.org/p> \"x\" .\n",
+                "_:c14n1 <http://example.org/q> _:c14n0 .\n",
+            ),
+        ),
+        (
+            // The "shared hashes" example from RDF Dataset Canonicalization: e0 and e1 share a
+            // first-degree hash and are only told apart by hashing the paths to e2 and e3.
+            json!({
+                "@graph": [
+                    {
+                        "@id": "http://example.com/#p",
+                        "http://example.com/#q": [{"@id": "_:e0"}, {"@id": "_:e1"}]
+                    },
+                    {"@id": "_:e0", "http://example.com/#p": {"@id": "_:e2"}},
+                    {"@id": "_:e1", "http://example.com/#p": {"@id": "_:e3"}},
+                    {"@id": "_:e2", "http://example.com/#r": {"@id": "_:e3"}}
+                ]
+            }),
+            concat!(
+                "<http://example.com/#p> <http://example.com/#q> _:c14n2 .\n",
+                "<http://example.com/#p> <http://example.com/#q> _:c14n3 .\n",
+                "_:c14n0 <http://example.com/#r> _:c14n1 .\n",
+                "_:c14n2 <http://example.com/#p> _:c14n1 .\n",
+                "_:c14n3 <http://example.com/#p> _:c14n0 .\n",
+            ),
+        ),
+    ];
+
+    for (input, expected) in vectors {
+        assert_eq!(canonicalize(&input).unwrap(), expected, "{}", input);
+    }
+}
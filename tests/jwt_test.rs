@@ -0,0 +1,231 @@
+use base64::Engine;
+use common::{serve, Chain};
+use ethers::types::Address;
+use ethers::utils::keccak256;
+use fi_ethr_resolver::{verify_jwt, JwtValidation, ProofPurpose};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+mod common;
+
+const NOW: u64 = 1_700_000_000;
+
+fn secret_key() -> SecretKey {
+    SecretKey::from_slice(&[7u8; 32]).unwrap()
+}
+
+fn public_key_did() -> String {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key());
+    format!("did:ethr:0x{}", hex::encode(public_key.serialize()))
+}
+
+fn address_did() -> String {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key());
+    let address = Address::from_slice(&keccak256(&public_key.serialize_uncompressed()[1..])[12..]);
+    format!("did:ethr:{:#x}", address)
+}
+
+fn sign(header: Value, payload: Value, secret_key: &SecretKey) -> String {
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let signing_input = format!(
+        "{}.{}",
+        engine.encode(header.to_string()),
+        engine.encode(payload.to_string())
+    );
+
+    let digest: [u8; 32] = Sha256::digest(signing_input.as_bytes()).into();
+    let message = Message::from_digest(digest);
+
+    let signature = match header["alg"].as_str() {
+        Some("ES256K-R") => {
+            let (recovery_id, signature) = Secp256k1::new()
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut signature = signature.to_vec();
+            signature.push(recovery_id.to_i32() as u8);
+            signature
+        }
+        _ => Secp256k1::new()
+            .sign_ecdsa(&message, secret_key)
+            .serialize_compact()
+            .to_vec(),
+    };
+
+    format!("{}.{}", signing_input, engine.encode(signature))
+}
+
+fn validation() -> JwtValidation {
+    JwtValidation {
+        now: Some(NOW),
+        ..Default::default()
+    }
+}
+
+async fn verify(jwt: &str, provider: &str, validation: &JwtValidation) -> Result<String, String> {
+    match verify_jwt(jwt, ProofPurpose::AssertionMethod, provider, validation).await {
+        Ok(val) => Ok(val.verification_method),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[tokio::test]
+pub async fn es256k_jwt_is_verified_against_the_controller_key() {
+    let provider = serve(Chain::new(1, 100).into_mock()).await;
+    let did = public_key_did();
+    let payload = json!({"iss": did, "iat": NOW});
+
+    let jwt = sign(
+        json!({"alg": "ES256K", "typ": "JWT", "kid": format!("{}#controllerKey", did)}),
+        payload.clone(),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(&jwt, &provider, &validation()).await,
+        Ok(format!("{}#controllerKey", did))
+    );
+
+    let forged = sign(
+        json!({"alg": "ES256K", "typ": "JWT"}),
+        payload.clone(),
+        &SecretKey::from_slice(&[9u8; 32]).unwrap(),
+    );
+    assert_eq!(
+        verify(&forged, &provider, &validation()).await,
+        Err(format!(
+            "No assertionMethod verification method of {} matches the JWT signature",
+            did
+        ))
+    );
+
+    let wrong_kid = sign(
+        json!({"alg": "ES256K", "typ": "JWT", "kid": format!("{}#delegate-1", did)}),
+        payload,
+        &secret_key(),
+    );
+    assert!(verify(&wrong_kid, &provider, &validation()).await.is_err());
+}
+
+#[tokio::test]
+pub async fn es256k_r_jwt_is_verified_against_the_controller_address() {
+    let provider = serve(Chain::new(1, 100).into_mock()).await;
+    let did = address_did();
+    let payload = json!({"iss": did, "iat": NOW});
+
+    let jwt = sign(
+        json!({"alg": "ES256K-R", "typ": "JWT"}),
+        payload.clone(),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(&jwt, &provider, &validation()).await,
+        Ok(format!("{}#controller", did))
+    );
+
+    let forged = sign(
+        json!({"alg": "ES256K-R", "typ": "JWT"}),
+        payload.clone(),
+        &SecretKey::from_slice(&[9u8; 32]).unwrap(),
+    );
+    assert!(verify(&forged, &provider, &validation()).await.is_err());
+
+    let wrong_kid = sign(
+        json!({"alg": "ES256K-R", "typ": "JWT", "kid": "#controllerKey"}),
+        payload,
+        &secret_key(),
+    );
+    assert!(verify(&wrong_kid, &provider, &validation()).await.is_err());
+}
+
+#[tokio::test]
+pub async fn time_and_audience_claims_are_enforced() {
+    let did = address_did();
+    let header = json!({"alg": "ES256K-R", "typ": "JWT"});
+    let unreachable = "http://127.0.0.1:1";
+
+    let expired = sign(
+        header.clone(),
+        json!({"iss": did, "exp": NOW - 301}),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(&expired, unreachable, &validation()).await,
+        Err(String::from("JWT has expired"))
+    );
+
+    let premature = sign(
+        header.clone(),
+        json!({"iss": did, "nbf": NOW + 301}),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(&premature, unreachable, &validation()).await,
+        Err(String::from("JWT is not valid yet"))
+    );
+
+    let strict = JwtValidation {
+        clock_skew: Duration::ZERO,
+        ..validation()
+    };
+    let issued_later = sign(
+        header.clone(),
+        json!({"iss": did, "iat": NOW + 1}),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(
+            &issued_later,
+            unreachable,
+            &JwtValidation {
+                reject_future_iat: true,
+                ..strict.clone()
+            }
+        )
+        .await,
+        Err(String::from("JWT was issued in the future"))
+    );
+
+    let provider = serve(Chain::new(1, 100).into_mock()).await;
+    assert_eq!(
+        verify(&issued_later, &provider, &strict).await,
+        Ok(format!("{}#controller", did))
+    );
+
+    let audience = sign(
+        header,
+        json!({"iss": did, "aud": ["did:example:verifier"]}),
+        &secret_key(),
+    );
+    assert_eq!(
+        verify(&audience, unreachable, &validation()).await,
+        Err(String::from(
+            "JWT has an 'aud' claim but no audience was expected"
+        ))
+    );
+    assert_eq!(
+        verify(
+            &audience,
+            unreachable,
+            &JwtValidation {
+                audience: Some(String::from("did:example:other")),
+                ..validation()
+            }
+        )
+        .await,
+        Err(String::from(
+            "JWT audience does not include did:example:other"
+        ))
+    );
+
+    assert!(verify(
+        &audience,
+        &provider,
+        &JwtValidation {
+            audience: Some(String::from("did:example:verifier")),
+            ..validation()
+        }
+    )
+    .await
+    .is_ok());
+}
@@ -0,0 +1,170 @@
+use base64::Engine;
+use common::{serve, Chain};
+use ethers::types::Address;
+use ethers::utils::keccak256;
+use fi_ethr_resolver::{canonicalize, verify_ld_proof, LdProofVerification};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+mod common;
+
+fn signer() -> (SecretKey, String) {
+    let secret_key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+    let address = Address::from_slice(&keccak256(&public_key.serialize_uncompressed()[1..])[12..]);
+
+    (secret_key, format!("did:ethr:{:#x}", address))
+}
+
+fn credential(issuer: &str) -> Value {
+    json!({
+        "@context": [
+            "https://www.w3.org/2018/credentials/v1",
+            "https://w3id.org/security/suites/secp256k1recovery-2020/v2"
+        ],
+        "type": ["VerifiableCredential"],
+        "issuer": issuer,
+        "issuanceDate": "2024-01-01T00:00:00Z",
+        "credentialSubject": {"id": "did:example:subject"}
+    })
+}
+
+fn sign(mut document: Value, secret_key: &SecretKey, verification_method: &str) -> Value {
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+
+    let mut proof = json!({
+        "type": "EcdsaSecp256k1RecoverySignature2020",
+        "created": "2024-01-01T00:00:00Z",
+        "proofPurpose": "assertionMethod",
+        "verificationMethod": verification_method
+    });
+
+    let mut options = proof.clone();
+    options["@context"] = document["@context"].clone();
+
+    let mut verify_data = Sha256::digest(canonicalize(&options).unwrap().as_bytes()).to_vec();
+    verify_data.extend(Sha256::digest(canonicalize(&document).unwrap().as_bytes()));
+
+    let header = engine.encode(r#"{"alg":"ES256K-R","b64":false,"crit":["b64"]}"#);
+    let mut signing_input = format!("{}.", header).into_bytes();
+    signing_input.extend(verify_data);
+
+    let digest: [u8; 32] = Sha256::digest(&signing_input).into();
+    let (recovery_id, signature) = Secp256k1::new()
+        .sign_ecdsa_recoverable(&Message::from_digest(digest), secret_key)
+        .serialize_compact();
+
+    let mut signature = signature.to_vec();
+    signature.push(recovery_id.to_i32() as u8);
+
+    proof["jws"] = Value::from(format!("{}..{}", header, engine.encode(signature)));
+    document["proof"] = proof;
+    document
+}
+
+#[tokio::test]
+pub async fn signed_credential_is_verified_and_tampering_is_detected() {
+    let provider = serve(Chain::new(1, 100).into_mock()).await;
+    let (secret_key, did) = signer();
+    let verification_method = format!("{}#controller", did);
+
+    let signed = sign(credential(&did), &secret_key, &verification_method);
+
+    assert_eq!(
+        verify_ld_proof(&signed, &provider).await.unwrap(),
+        vec![LdProofVerification {
+            proof_type: String::from("EcdsaSecp256k1RecoverySignature2020"),
+            proof_purpose: String::from("assertionMethod"),
+            verification_method: verification_method.clone(),
+        }]
+    );
+
+    let mut tampered = signed.clone();
+    tampered["issuanceDate"] = Value::from("2025-01-01T00:00:00Z");
+    assert_eq!(
+        verify_ld_proof(&tampered, &provider)
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        format!(
+            "Proof signature does not match {} for assertionMethod",
+            verification_method
+        )
+    );
+
+    let other_key = SecretKey::from_slice(&[9u8; 32]).unwrap();
+    let forged = sign(credential(&did), &other_key, &verification_method);
+    assert!(verify_ld_proof(&forged, &provider).await.is_err());
+}
+
+#[tokio::test]
+pub async fn independently_signed_credential_is_verified() {
+    // Signed outside this crate: the N-Quads were derived by hand from the bundled contexts and
+    // signed with a separate secp256k1 implementation, so neither the canonical form nor the
+    // signing input comes from `sign`.
+    let provider = serve(Chain::new(1, 100).into_mock()).await;
+    let did = "did:ethr:0xfc700a83d2f901b437ed966bae685b5780e886db";
+
+    let signed = json!({
+        "@context": [
+            "https://www.w3.org/2018/credentials/v1",
+            "https://w3id.org/security/suites/secp256k1recovery-2020/v2"
+        ],
+        "type": ["VerifiableCredential"],
+        "issuer": did,
+        "issuanceDate": "2020-04-11T21:07:06Z",
+        "credentialSubject": {"id": "did:example:ebfeb1f712ebc6f1c276e12ec21"},
+        "proof": {
+            "type": "EcdsaSecp256k1RecoverySignature2020",
+            "created": "2020-04-11T21:07:06Z",
+            "proofPurpose": "assertionMethod",
+            "verificationMethod": format!("{}#controller", did),
+            "jws": "eyJhbGciOiJFUzI1NkstUiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19..oPp0c9ZCFG3f1cKA_NtSqmt-QMLH9IBe8jjD6y9oWZNraoi-SBHfbaz66v6Xa07caeMpLr1wNggC993lJWpbJAE"
+        }
+    });
+
+    let mut unsigned = signed.clone();
+    unsigned.as_object_mut().unwrap().remove("proof");
+    assert_eq!(
+        canonicalize(&unsigned).unwrap(),
+        format!(
+            concat!(
+                "_:c14n0 <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> .\n",
+                "_:c14n0 <https://www.w3.org/2018/credentials#credentialSubject> <did:example:ebfeb1f712ebc6f1c276e12ec21> .\n",
+                "_:c14n0 <https://www.w3.org/2018/credentials#issuanceDate> \"2020-04-11T21:07:06Z\"^^<http://www.w3.org/2001/XMLSchema#dateTime> .\n",
+                "_:c14n0 <https://www.w3.org/2018/credentials#issuer> <{}> .\n",
+            ),
+            did
+        )
+    );
+
+    assert_eq!(
+        verify_ld_proof(&signed, &provider).await.unwrap(),
+        vec![LdProofVerification {
+            proof_type: String::from("EcdsaSecp256k1RecoverySignature2020"),
+            proof_purpose: String::from("assertionMethod"),
+            verification_method: format!("{}#controller", did),
+        }]
+    );
+}
+
+#[tokio::test]
+pub async fn undefined_terms_are_rejected_before_verification() {
+    let (secret_key, did) = signer();
+    let verification_method = format!("{}#controller", did);
+
+    let mut document = credential(&did);
+    document["credentialSubject"]["alumniOf"] = Value::from("Example University");
+    let signed = sign(document, &secret_key, &verification_method);
+
+    assert_eq!(
+        verify_ld_proof(&signed, "http://127.0.0.1:1")
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The signed document uses undefined JSON-LD terms: alumniOf"
+    );
+}
@@ -0,0 +1,145 @@
+use common::owner_changed;
+use ethers::types::{Address, Log};
+use ethers::utils::keccak256;
+use fi_ethr_resolver::{resolve_from_logs, OutputProfile};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde_json::{json, Value};
+
+mod common;
+
+fn controller_key() -> (String, Address) {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+
+    let hashed = keccak256(&public_key.serialize_uncompressed()[1..]);
+    let address = Address::from_slice(&hashed[12..]);
+
+    (hex::encode(public_key.serialize()), address)
+}
+
+fn resolve_document(did: &str, logs: Vec<Log>) -> (Value, Value) {
+    let result = match resolve_from_logs(
+        did,
+        "application/did+json",
+        1,
+        None,
+        logs,
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    (
+        result.document().unwrap(),
+        serde_json::to_value(&result.did_document_metadata).unwrap(),
+    )
+}
+
+#[test]
+pub fn public_key_did_without_owner_change() {
+    let (public_key, address) = controller_key();
+    let did = format!("did:ethr:0x{}", public_key);
+
+    let (did_doc, _metadata) = resolve_document(&did, Vec::new());
+
+    assert_eq!(
+        did_doc["verificationMethod"],
+        json!([
+            {
+                "id": format!("{}#controller", did),
+                "type": "EcdsaSecp256k1RecoveryMethod2020",
+                "controller": did,
+                "blockchainAccountId": format!("eip155:1:{:#x}", address),
+                "revoked": false
+            },
+            {
+                "id": format!("{}#controllerKey", did),
+                "type": "EcdsaSecp256k1VerificationKey2019",
+                "controller": did,
+                "publicKeyHex": public_key
+            }
+        ])
+    );
+
+    let relationships = json!([
+        format!("{}#controller", did),
+        format!("{}#controllerKey", did)
+    ]);
+    assert_eq!(did_doc["authentication"], relationships);
+    assert_eq!(did_doc["assertionMethod"], relationships);
+}
+
+#[test]
+pub fn public_key_did_after_owner_change() {
+    let (public_key, address) = controller_key();
+    let did = format!("did:ethr:0x{}", public_key);
+    let owner = Address::repeat_byte(0x11);
+
+    let (did_doc, _metadata) = resolve_document(&did, vec![owner_changed(address, owner, 0, 10)]);
+
+    assert_eq!(
+        did_doc["verificationMethod"],
+        json!([
+            {
+                "id": format!("{}#controller", did),
+                "type": "EcdsaSecp256k1RecoveryMethod2020",
+                "controller": did,
+                "blockchainAccountId": format!("eip155:1:{:#x}", owner),
+                "revoked": false
+            }
+        ])
+    );
+
+    let relationships = json!([format!("{}#controller", did)]);
+    assert_eq!(did_doc["authentication"], relationships);
+    assert_eq!(did_doc["assertionMethod"], relationships);
+}
+
+#[test]
+pub fn public_key_did_after_owner_returns_to_key() {
+    let (public_key, address) = controller_key();
+    let did = format!("did:ethr:0x{}", public_key);
+    let owner = Address::repeat_byte(0x11);
+
+    let (did_doc, _metadata) = resolve_document(
+        &did,
+        vec![
+            owner_changed(address, owner, 0, 10),
+            owner_changed(address, address, 10, 20),
+        ],
+    );
+
+    let relationships = json!([
+        format!("{}#controller", did),
+        format!("{}#controllerKey", did)
+    ]);
+    assert_eq!(did_doc["authentication"], relationships);
+    assert_eq!(did_doc["assertionMethod"], relationships);
+}
+
+#[test]
+pub fn address_did_never_has_controller_key() {
+    let (_public_key, address) = controller_key();
+    let did = format!("did:ethr:{:#x}", address);
+
+    let (did_doc, _metadata) = resolve_document(&did, Vec::new());
+
+    let relationships = json!([format!("{}#controller", did)]);
+    assert_eq!(did_doc["authentication"], relationships);
+    assert_eq!(did_doc["assertionMethod"], relationships);
+}
+
+#[test]
+pub fn owner_change_to_null_address_deactivates() {
+    let (public_key, address) = controller_key();
+    let did = format!("did:ethr:0x{}", public_key);
+
+    let (did_doc, metadata) =
+        resolve_document(&did, vec![owner_changed(address, Address::zero(), 0, 10)]);
+
+    assert_eq!(did_doc, json!({ "id": did }));
+    assert_eq!(metadata["deactivated"], json!(true));
+    assert_eq!(metadata["versionId"], json!("10"));
+}
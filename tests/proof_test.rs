@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use common::{owner_changed, registry, RpcMock};
+use ethers::abi::{encode, Token};
+use ethers::types::{
+    Address, Block, Bytes, EIP1186ProofResponse, StorageProof, TransactionReceipt, H256, H64, U256,
+};
+use ethers::utils::keccak256;
+use ethers::utils::rlp::{self, RlpStream};
+use fi_ethr_resolver::{
+    encode_receipt, header_hash, ordered_trie_root, verify_trie_proof, EthrResolver, NetworkConfig,
+    DID_JSON, MAX_HEADER_WALK,
+};
+use serde_json::Value;
+
+mod common;
+
+const PROVIDER: &str = "http://127.0.0.1:8545";
+const EMPTY_ROOT: &str = "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421";
+
+fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&path.to_vec());
+    stream.append(&value.to_vec());
+    stream.out().to_vec()
+}
+
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 };
+    let (mut encoded, rest) = match nibbles.len() % 2 {
+        1 => (vec![((flag + 1) << 4) | nibbles[0]], &nibbles[1..]),
+        _ => (vec![flag << 4], nibbles),
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+fn hash(node: &[u8]) -> H256 {
+    H256(keccak256(node))
+}
+
+fn branch(children: &[(u8, H256)]) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(17);
+    for nibble in 0..16u8 {
+        match children.iter().find(|(index, _child)| *index == nibble) {
+            Some((_index, child)) => stream.append(child),
+            None => stream.append_empty_data(),
+        };
+    }
+    stream.append_empty_data();
+    stream.out().to_vec()
+}
+
+fn extension(path: &[u8], child: H256) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(&hex_prefix(path, false));
+    stream.append(&child);
+    stream.out().to_vec()
+}
+
+fn proof(nodes: &[&Vec<u8>]) -> Vec<Bytes> {
+    nodes
+        .iter()
+        .map(|node| Bytes::from((*node).clone()))
+        .collect()
+}
+
+/// Finds a key whose hashed path satisfies `accept`, so tests can place keys
+/// under chosen branches of a secure trie.
+fn find_key(accept: impl Fn(&[u8]) -> bool) -> Vec<u8> {
+    (0u64..)
+        .map(|val| val.to_be_bytes().to_vec())
+        .find(|key| accept(&nibbles(&keccak256(key))))
+        .unwrap()
+}
+
+#[test]
+pub fn header_hash_matches_mainnet_genesis() {
+    let genesis = Block::<H256> {
+        uncles_hash: H256::from_str(
+            "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+        )
+        .unwrap(),
+        author: Some(Default::default()),
+        state_root: H256::from_str(
+            "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+        )
+        .unwrap(),
+        transactions_root: H256::from_str(EMPTY_ROOT).unwrap(),
+        receipts_root: H256::from_str(EMPTY_ROOT).unwrap(),
+        logs_bloom: Some(Default::default()),
+        difficulty: U256::from(0x400000000u64),
+        number: Some(0u64.into()),
+        gas_limit: U256::from(5000),
+        extra_data: Bytes::from_str(
+            "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+        )
+        .unwrap(),
+        mix_hash: Some(Default::default()),
+        nonce: Some(H64::from_low_u64_be(0x42)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        header_hash(&genesis),
+        H256::from_str("0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+            .unwrap()
+    );
+}
+
+#[test]
+pub fn ordered_trie_root_encodes_receipt_indexes() {
+    assert_eq!(ordered_trie_root(&[]), H256::from_str(EMPTY_ROOT).unwrap());
+
+    let value = vec![0xab; 40];
+    assert_eq!(
+        ordered_trie_root(std::slice::from_ref(&value)),
+        H256(keccak256(leaf(&[0x20, 0x80], &value)))
+    );
+}
+
+#[test]
+pub fn trie_proofs_are_verified_against_the_root() {
+    let key = [0x11u8; 20];
+    let value = vec![0x2a];
+
+    let mut path = vec![0x20];
+    path.extend(keccak256(key));
+    let node = leaf(&path, &value);
+    let root = H256(keccak256(&node));
+    let proof = vec![Bytes::from(node)];
+
+    assert_eq!(verify_trie_proof(root, &key, &proof).unwrap(), Some(value));
+    assert_eq!(
+        verify_trie_proof(root, &[0x22u8; 20], &proof).unwrap(),
+        None
+    );
+    assert_eq!(
+        verify_trie_proof(root, &key, &[])
+            .err()
+            .unwrap()
+            .to_string(),
+        "The proof ended before the key was resolved"
+    );
+    assert_eq!(
+        verify_trie_proof(H256::zero(), &key, &proof)
+            .err()
+            .unwrap()
+            .to_string(),
+        "The proof does not match the expected trie root"
+    );
+    assert_eq!(
+        verify_trie_proof(H256::from_str(EMPTY_ROOT).unwrap(), &key, &[]).unwrap(),
+        None
+    );
+}
+
+#[test]
+pub fn branch_and_extension_nodes_are_followed() {
+    let first = find_key(|_path| true);
+    let first_path = nibbles(&keccak256(&first));
+    let sibling = find_key(|path| path[0] == first_path[0] && path[1] != first_path[1]);
+    let sibling_path = nibbles(&keccak256(&sibling));
+    let other = find_key(|path| path[0] != first_path[0]);
+    let other_path = nibbles(&keccak256(&other));
+    let missing = find_key(|path| path[0] != first_path[0] && path[0] != other_path[0]);
+
+    let first_leaf = leaf(&hex_prefix(&first_path[1..], true), b"first value");
+    let other_leaf = leaf(&hex_prefix(&other_path[1..], true), b"other value");
+    let root_branch = branch(&[
+        (first_path[0], hash(&first_leaf)),
+        (other_path[0], hash(&other_leaf)),
+    ]);
+    let root = hash(&root_branch);
+
+    assert_eq!(
+        verify_trie_proof(root, &first, &proof(&[&root_branch, &first_leaf])).unwrap(),
+        Some(b"first value".to_vec())
+    );
+    assert_eq!(
+        verify_trie_proof(root, &other, &proof(&[&root_branch, &other_leaf])).unwrap(),
+        Some(b"other value".to_vec())
+    );
+    assert_eq!(
+        verify_trie_proof(root, &missing, &proof(&[&root_branch])).unwrap(),
+        None
+    );
+    assert_eq!(
+        verify_trie_proof(root, &first, &proof(&[&root_branch, &other_leaf]))
+            .err()
+            .unwrap()
+            .to_string(),
+        "The proof does not match the expected trie root"
+    );
+
+    let first_leaf = leaf(&hex_prefix(&first_path[2..], true), b"first value");
+    let sibling_leaf = leaf(&hex_prefix(&sibling_path[2..], true), b"sibling value");
+    let inner_branch = branch(&[
+        (first_path[1], hash(&first_leaf)),
+        (sibling_path[1], hash(&sibling_leaf)),
+    ]);
+    let root_extension = extension(&first_path[..1], hash(&inner_branch));
+    let root = hash(&root_extension);
+
+    assert_eq!(
+        verify_trie_proof(
+            root,
+            &sibling,
+            &proof(&[&root_extension, &inner_branch, &sibling_leaf])
+        )
+        .unwrap(),
+        Some(b"sibling value".to_vec())
+    );
+    assert_eq!(
+        verify_trie_proof(
+            root,
+            &first,
+            &proof(&[&root_extension, &inner_branch, &first_leaf])
+        )
+        .unwrap(),
+        Some(b"first value".to_vec())
+    );
+    assert_eq!(
+        verify_trie_proof(root, &other, &proof(&[&root_extension])).unwrap(),
+        None
+    );
+    assert_eq!(
+        verify_trie_proof(root, &sibling, &proof(&[&root_extension, &inner_branch]))
+            .err()
+            .unwrap()
+            .to_string(),
+        "The proof ended before the key was resolved"
+    );
+}
+
+#[test]
+pub fn ordered_trie_root_covers_several_receipts() {
+    let values = [vec![0xa0; 40], vec![0xa1; 40], vec![0xa2; 40]];
+
+    // Index keys rlp(0) = 0x80, rlp(1) = 0x01 and rlp(2) = 0x02 split on the
+    // first nibble; 0x01 and 0x02 then share a branch at nibble 0.
+    let zero = leaf(&hex_prefix(&[0], true), &values[0]);
+    let one = leaf(&hex_prefix(&[], true), &values[1]);
+    let two = leaf(&hex_prefix(&[], true), &values[2]);
+    let low = branch(&[(1, hash(&one)), (2, hash(&two))]);
+    let root = branch(&[(0, hash(&low)), (8, hash(&zero))]);
+
+    assert_eq!(ordered_trie_root(&values), hash(&root));
+    assert_ne!(
+        ordered_trie_root(&[values[1].clone(), values[0].clone(), values[2].clone()]),
+        hash(&root)
+    );
+}
+
+#[test]
+pub fn typed_receipts_are_prefixed_with_their_type() {
+    let receipt = TransactionReceipt {
+        status: Some(1u64.into()),
+        cumulative_gas_used: U256::from(0x5208),
+        ..Default::default()
+    };
+
+    let mut legacy = hex::decode("f9010801825208b90100").unwrap();
+    legacy.extend([0u8; 256]);
+    legacy.push(0xc0);
+    assert_eq!(encode_receipt(&receipt), legacy);
+
+    let mut typed = vec![0x02];
+    typed.extend(&legacy);
+    assert_eq!(
+        encode_receipt(&TransactionReceipt {
+            transaction_type: Some(2u64.into()),
+            ..receipt.clone()
+        }),
+        typed
+    );
+    assert_eq!(
+        encode_receipt(&TransactionReceipt {
+            transaction_type: Some(0u64.into()),
+            ..receipt
+        }),
+        legacy
+    );
+}
+
+struct VerifiedChain {
+    blocks: Vec<Block<H256>>,
+    receipts: HashMap<u64, Vec<TransactionReceipt>>,
+    proof: EIP1186ProofResponse,
+}
+
+impl VerifiedChain {
+    /// Links headers 0..=`latest`, with one owner change of `identity` in
+    /// `change_block` whose receipt, account proof and slot-2 storage proof
+    /// all check out.
+    fn new(identity: Address, owner: Address, change_block: u64, latest: u64) -> VerifiedChain {
+        let slot = keccak256(encode(&[Token::Address(identity), Token::Uint(2.into())]));
+        let storage_leaf = leaf(
+            &hex_prefix(&nibbles(&keccak256(slot)), true),
+            &rlp::encode(&U256::from(change_block)),
+        );
+
+        let mut account = RlpStream::new_list(4);
+        account.append(&0u64);
+        account.append(&0u64);
+        account.append(&hash(&storage_leaf));
+        account.append(&H256(keccak256([])));
+        let account_leaf = leaf(
+            &hex_prefix(&nibbles(&keccak256(registry())), true),
+            &account.out(),
+        );
+
+        let receipt = TransactionReceipt {
+            transaction_hash: H256::from_low_u64_be(change_block),
+            block_number: Some(change_block.into()),
+            status: Some(1u64.into()),
+            cumulative_gas_used: U256::from(50_000),
+            logs: vec![owner_changed(identity, owner, 0, change_block)],
+            transaction_type: Some(2u64.into()),
+            ..Default::default()
+        };
+
+        let mut blocks = Vec::<Block<H256>>::new();
+        for number in 0..=latest {
+            let mut block = Block::<H256> {
+                parent_hash: blocks.last().and_then(|val| val.hash).unwrap_or_default(),
+                author: Some(Default::default()),
+                state_root: hash(&account_leaf),
+                transactions_root: H256::from_str(EMPTY_ROOT).unwrap(),
+                receipts_root: match number == change_block {
+                    true => ordered_trie_root(&[encode_receipt(&receipt)]),
+                    false => H256::from_str(EMPTY_ROOT).unwrap(),
+                },
+                logs_bloom: Some(Default::default()),
+                number: Some(number.into()),
+                timestamp: U256::from(1_000 + number),
+                mix_hash: Some(Default::default()),
+                nonce: Some(Default::default()),
+                ..Default::default()
+            };
+            block.hash = Some(header_hash(&block));
+            blocks.push(block);
+        }
+
+        VerifiedChain {
+            blocks,
+            receipts: HashMap::from([(change_block, vec![receipt])]),
+            proof: EIP1186ProofResponse {
+                address: registry(),
+                account_proof: vec![Bytes::from(account_leaf)],
+                storage_proof: vec![StorageProof {
+                    key: U256::from(slot),
+                    proof: vec![Bytes::from(storage_leaf)],
+                    value: U256::from(change_block),
+                }],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn hash(&self, number: u64) -> H256 {
+        self.blocks[number as usize].hash.unwrap()
+    }
+
+    fn into_mock(self) -> RpcMock {
+        RpcMock::new(move |method, params| match method {
+            "eth_chainId" => Some(Value::from("0x539")),
+            "eth_getBlockByHash" => {
+                let hash = H256::from_str(params[0].as_str()?).ok()?;
+                self.blocks
+                    .iter()
+                    .find(|block| block.hash == Some(hash))
+                    .map(|block| serde_json::to_value(block).unwrap())
+            }
+            "eth_getBlockReceipts" => {
+                let number =
+                    u64::from_str_radix(params[0].as_str()?.trim_start_matches("0x"), 16).ok()?;
+                Some(
+                    serde_json::to_value(self.receipts.get(&number).cloned().unwrap_or_default())
+                        .unwrap(),
+                )
+            }
+            "eth_getProof" => Some(serde_json::to_value(&self.proof).unwrap()),
+            _ => None,
+        })
+    }
+}
+
+fn verified_resolver(mock: &RpcMock) -> EthrResolver<RpcMock> {
+    let client = mock.clone();
+    EthrResolver::builder()
+        .network(NetworkConfig::new("dev", PROVIDER))
+        .build_with(move |_network| Ok(client.clone()))
+        .unwrap()
+}
+
+#[tokio::test]
+pub async fn verified_resolution_proves_the_registry_history() {
+    let identity = Address::repeat_byte(0x11);
+    let owner = Address::repeat_byte(0x22);
+    let did = format!("did:ethr:dev:{:#x}", identity);
+
+    let chain = VerifiedChain::new(identity, owner, 3, 6);
+    let anchor = chain.hash(6);
+    let mock = chain.into_mock();
+
+    let result = verified_resolver(&mock)
+        .resolve_verified(&did, DID_JSON, anchor, &[])
+        .await
+        .unwrap();
+    assert_eq!(
+        result.document().unwrap()["verificationMethod"][0]["blockchainAccountId"],
+        format!("eip155:1337:{:#x}", owner)
+    );
+    assert_eq!(mock.calls("eth_getProof"), 1);
+    assert_eq!(mock.calls("eth_getBlockReceipts"), 1);
+    assert_eq!(mock.calls("eth_getBlockByHash"), 4);
+
+    let mut tampered = VerifiedChain::new(identity, owner, 3, 6);
+    let storage_proof = &mut tampered.proof.storage_proof[0];
+    let mut node = storage_proof.proof[0].to_vec();
+    *node.last_mut().unwrap() = 4;
+    storage_proof.proof[0] = Bytes::from(node);
+    let anchor = tampered.hash(6);
+
+    assert_eq!(
+        verified_resolver(&tampered.into_mock())
+            .resolve_verified(&did, DID_JSON, anchor, &[])
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The proof does not match the expected trie root"
+    );
+
+    let mut forged = VerifiedChain::new(identity, owner, 3, 6);
+    forged.receipts.get_mut(&3).unwrap()[0].cumulative_gas_used = U256::from(1);
+    let anchor = forged.hash(6);
+
+    assert_eq!(
+        verified_resolver(&forged.into_mock())
+            .resolve_verified(&did, DID_JSON, anchor, &[])
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The receipts returned for block 3 do not match its receipts root"
+    );
+}
+
+#[tokio::test]
+pub async fn header_walk_is_bounded_and_extended_by_checkpoints() {
+    let identity = Address::repeat_byte(0x11);
+    let owner = Address::repeat_byte(0x22);
+    let did = format!("did:ethr:dev:{:#x}", identity);
+
+    let latest = 3 + MAX_HEADER_WALK + 1;
+    let chain = VerifiedChain::new(identity, owner, 3, latest);
+    let anchor = chain.hash(latest);
+    let checkpoint = chain.hash(10);
+    let mock = chain.into_mock();
+    let resolver = verified_resolver(&mock);
+
+    assert_eq!(
+        resolver
+            .resolve_verified(&did, DID_JSON, anchor, &[])
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        format!(
+            "Block 3 is more than {} headers below the nearest trusted block; pass a checkpoint closer to it",
+            MAX_HEADER_WALK
+        )
+    );
+
+    let result = resolver
+        .resolve_verified(&did, DID_JSON, anchor, &[checkpoint])
+        .await
+        .unwrap();
+    assert_eq!(
+        result.document().unwrap()["verificationMethod"][0]["blockchainAccountId"],
+        format!("eip155:1337:{:#x}", owner)
+    );
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "python")]
+
+use common::{attribute_changed, serve, Chain};
+use ethers::types::Address;
+use fi_ethr_resolver::PyResolver;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+mod common;
+
+const DID: &str = "did:ethr:0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+fn to_json(value: &Bound<'_, PyAny>) -> serde_json::Value {
+    let json = value
+        .py()
+        .import_bound("json")
+        .unwrap()
+        .call_method1("dumps", (value,))
+        .unwrap();
+
+    serde_json::from_str(json.extract::<&str>().unwrap()).unwrap()
+}
+
+#[test]
+pub fn resolve_and_resolve_with_metadata_return_the_same_document() {
+    let identity = DID.rsplit(':').next().unwrap().parse::<Address>().unwrap();
+
+    let mut chain = Chain::new(1, 100);
+    chain.changed.insert(identity, 10);
+    chain.logs = vec![attribute_changed(
+        identity,
+        "did/svc/HubService",
+        b"https://hubs.example.com",
+        0,
+        10,
+    )];
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let provider = runtime.block_on(serve(chain.into_mock()));
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let resolver = py
+            .get_type_bound::<PyResolver>()
+            .call1((provider.as_str(),))
+            .unwrap();
+
+        let document = to_json(&resolver.call_method1("resolve", (DID,)).unwrap());
+        let result = resolver
+            .call_method1("resolve_with_metadata", (DID,))
+            .unwrap();
+
+        assert!(result.downcast::<PyDict>().is_ok());
+        assert_eq!(to_json(&result)["didDocument"], document);
+
+        assert!(document.get("services").is_none());
+        assert_eq!(
+            document["service"][0]["serviceEndpoint"],
+            "https://hubs.example.com"
+        );
+        assert!(document["verificationMethod"][0]
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|val| !val.is_null()));
+    });
+}
@@ -0,0 +1,174 @@
+use common::attribute_changed;
+use ethers::types::Address;
+use fi_ethr_resolver::{resolve_from_logs, to_cbor, OutputProfile, DID_CBOR, DID_LD_JSON};
+use serde_json::{json, Map, Value};
+
+mod common;
+
+const PUBLIC_KEY_DID: &str =
+    "did:ethr:0x03fdd57adec3d438ea237fe46b33ee1e016eda6b585c3e27ea66686c2ea5358479";
+
+#[test]
+pub fn ld_json_context_follows_method_types() {
+    let result = match resolve_from_logs(
+        PUBLIC_KEY_DID,
+        DID_LD_JSON,
+        1,
+        None,
+        Vec::new(),
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    assert_eq!(
+        result.did_document.context,
+        vec![
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/suites/secp256k1recovery-2020/v2",
+            "https://w3id.org/security/suites/secp256k1-2019/v1",
+            "https://w3id.org/security/v3-unstable",
+        ]
+    );
+}
+
+#[test]
+pub fn cbor_is_accepted() {
+    let result = match resolve_from_logs(
+        PUBLIC_KEY_DID,
+        DID_CBOR,
+        1,
+        None,
+        Vec::new(),
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    assert!(result.did_document.context.is_empty());
+    assert_eq!(result.did_resolution_metadata.content_type, DID_CBOR);
+}
+
+#[test]
+pub fn cbor_map_keys_are_deterministic() {
+    let encoded = to_cbor(&json!({ "id": 1, "a": [true, null], "bb": -2, "c": 1.5 }));
+
+    assert_eq!(
+        hex::encode(encoded),
+        "a4616182f5f66163f93e006262622162696401"
+    );
+}
+
+#[test]
+pub fn cbor_representation_is_the_did_core_document() {
+    let logs = vec![attribute_changed(
+        Address::repeat_byte(0x11),
+        "did/svc/HubService",
+        b"https://hubs.example.com",
+        0,
+        1,
+    )];
+
+    let result = match resolve_from_logs(
+        PUBLIC_KEY_DID,
+        DID_CBOR,
+        1,
+        None,
+        logs,
+        &OutputProfile::default(),
+    ) {
+        Ok(val) => val,
+        Err(error) => panic!("{}", error),
+    };
+
+    let encoded = result.representation().unwrap();
+    let (document, rest) = decode_cbor(&encoded);
+    assert!(rest.is_empty());
+
+    let mut members = document
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    members.sort();
+    assert_eq!(
+        members,
+        vec![
+            "assertionMethod",
+            "authentication",
+            "id",
+            "service",
+            "verificationMethod"
+        ]
+    );
+
+    assert_eq!(
+        document["service"],
+        json!([{
+            "id": format!("{}#service-1", PUBLIC_KEY_DID),
+            "type": "HubService",
+            "serviceEndpoint": "https://hubs.example.com"
+        }])
+    );
+    for method in document["verificationMethod"].as_array().unwrap() {
+        assert!(method
+            .as_object()
+            .unwrap()
+            .values()
+            .all(|val| !val.is_null()));
+    }
+    assert_eq!(document, result.document().unwrap());
+}
+
+fn decode_cbor(bytes: &[u8]) -> (Value, &[u8]) {
+    let major = bytes[0] >> 5;
+    let (argument, mut rest) = match bytes[0] & 0x1f {
+        val @ 0..=23 => (val as u64, &bytes[1..]),
+        24 => (bytes[1] as u64, &bytes[2..]),
+        25 => (u16::from_be_bytes([bytes[1], bytes[2]]) as u64, &bytes[3..]),
+        26 => (
+            u32::from_be_bytes(bytes[1..5].try_into().unwrap()) as u64,
+            &bytes[5..],
+        ),
+        27 => (
+            u64::from_be_bytes(bytes[1..9].try_into().unwrap()),
+            &bytes[9..],
+        ),
+        val => panic!("Unsupported CBOR argument: {}", val),
+    };
+
+    match (major, bytes[0]) {
+        (0, _) => (Value::from(argument), rest),
+        (1, _) => (Value::from(-1 - argument as i64), rest),
+        (3, _) => {
+            let (text, rest) = rest.split_at(argument as usize);
+            (Value::from(std::str::from_utf8(text).unwrap()), rest)
+        }
+        (4, _) => {
+            let mut items = Vec::new();
+            for _ in 0..argument {
+                let (item, remaining) = decode_cbor(rest);
+                items.push(item);
+                rest = remaining;
+            }
+            (Value::Array(items), rest)
+        }
+        (5, _) => {
+            let mut map = Map::new();
+            for _ in 0..argument {
+                let (key, remaining) = decode_cbor(rest);
+                let (val, remaining) = decode_cbor(remaining);
+                map.insert(String::from(key.as_str().unwrap()), val);
+                rest = remaining;
+            }
+            (Value::Object(map), rest)
+        }
+        (7, 0xf4) => (Value::Bool(false), rest),
+        (7, 0xf5) => (Value::Bool(true), rest),
+        (7, 0xf6) => (Value::Null, rest),
+        (_, val) => panic!("Unsupported CBOR item: {:#x}", val),
+    }
+}
@@ -0,0 +1,450 @@
+use std::time::Duration;
+
+use common::{delegate_changed, owner_changed, Chain, RpcMock};
+use ethers::types::Address;
+use fi_ethr_resolver::{
+    AccountEncoding, AddressLink, AddressRole, EthrResolver, NetworkConfig, OutputProfile, DID_JSON,
+};
+use futures::StreamExt;
+
+mod common;
+
+const PROVIDER: &str = "http://127.0.0.1:8545";
+
+fn assert_shareable<T: Send + Sync + Clone>() {}
+
+#[test]
+pub fn resolver_is_shareable() {
+    assert_shareable::<EthrResolver>();
+}
+
+#[test]
+pub fn builder_validates_configuration() {
+    assert_eq!(
+        EthrResolver::builder().build().err().unwrap().to_string(),
+        "The DID resolver has no networks configured"
+    );
+
+    assert_eq!(
+        EthrResolver::builder()
+            .network(NetworkConfig::new("mainnet", PROVIDER))
+            .network(NetworkConfig::new("mainnet", PROVIDER))
+            .build()
+            .err()
+            .unwrap()
+            .to_string(),
+        "Network is configured more than once: mainnet"
+    );
+
+    let mut network = NetworkConfig::new("dev", PROVIDER);
+    network.registry = Some(String::from("0x1234"));
+    assert!(EthrResolver::builder().network(network).build().is_err());
+
+    assert!(EthrResolver::builder()
+        .network(NetworkConfig::new("mainnet", "not a url"))
+        .build()
+        .is_err());
+}
+
+#[tokio::test]
+pub async fn unconfigured_network_is_rejected() {
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("mainnet", PROVIDER))
+        .timeout(Duration::from_secs(1))
+        .cache_ttl(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        resolver
+            .resolve(
+                "did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a",
+                DID_JSON
+            )
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The DID resolver is not configured for network: sepolia"
+    );
+}
+
+#[tokio::test]
+pub async fn bulk_resolution_yields_every_did() {
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("mainnet", PROVIDER))
+        .build()
+        .unwrap();
+
+    let dids = vec![
+        String::from("did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a"),
+        String::from("did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a"),
+        String::from("did:ethr:0x1234"),
+    ];
+
+    let mut results = resolver
+        .resolve_many(dids, DID_JSON, 2)
+        .map(|(did, result)| (did, result.err().unwrap().to_string()))
+        .collect::<Vec<(String, String)>>()
+        .await;
+    results.sort();
+
+    assert_eq!(
+        results,
+        vec![
+            (
+                String::from("did:ethr:0x1234"),
+                String::from("Not a valid did:ethr: did:ethr:0x1234")
+            ),
+            (
+                String::from("did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a"),
+                String::from("The DID resolver is not configured for network: sepolia")
+            ),
+            (
+                String::from("did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a"),
+                String::from("The DID resolver is not configured for network: sepolia")
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+pub async fn delegate_check_validates_arguments() {
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("mainnet", PROVIDER))
+        .consistency_check(true)
+        .build()
+        .unwrap();
+
+    let did = "did:ethr:0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+    assert_eq!(
+        resolver
+            .is_valid_delegate(did, "a delegate type longer than bytes32", did)
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "Value does not fit in bytes32: a delegate type longer than bytes32"
+    );
+
+    assert!(resolver
+        .is_valid_delegate(did, "veriKey", "0x1234")
+        .await
+        .is_err());
+
+    assert_eq!(
+        resolver
+            .owner_of("did:ethr:sepolia:0xb9c5714089478a327f09197987f16f9e5d936e8a")
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The DID resolver is not configured for network: sepolia"
+    );
+}
+
+fn mock_resolver(chain: Chain, profile: OutputProfile) -> (EthrResolver<RpcMock>, RpcMock) {
+    let mock = chain.into_mock();
+    let client = mock.clone();
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("dev", PROVIDER))
+        .profile(profile)
+        .build_with(move |_network| Ok(client.clone()))
+        .unwrap();
+
+    (resolver, mock)
+}
+
+#[tokio::test]
+pub async fn pkh_uses_the_configured_chain_id() {
+    let (resolver, _mock) = mock_resolver(Chain::new(1337, 100), OutputProfile::default());
+    let address = "0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+    assert_eq!(
+        resolver
+            .to_pkh(&format!("did:ethr:dev:{}", address))
+            .await
+            .unwrap(),
+        format!("did:pkh:eip155:1337:{}", address)
+    );
+}
+
+fn walk_chain(identities: &[Address]) -> Chain {
+    let mut chain = Chain::new(1337, 100);
+    for identity in identities {
+        chain.changed.insert(*identity, 20);
+        chain
+            .logs
+            .push(owner_changed(*identity, Address::repeat_byte(0x33), 0, 10));
+        chain.logs.push(delegate_changed(
+            *identity,
+            "veriKey",
+            Address::repeat_byte(0x44),
+            2_000,
+            10,
+            20,
+        ));
+    }
+    chain
+}
+
+fn delayed_resolver(mock: &RpcMock, cache_ttl: Option<Duration>) -> EthrResolver<RpcMock> {
+    let client = mock.clone().with_delay(Duration::from_millis(20));
+    let mut builder = EthrResolver::builder().network(NetworkConfig::new("dev", PROVIDER));
+    if let Some(val) = cache_ttl {
+        builder = builder.cache_ttl(val);
+    }
+
+    builder
+        .build_with(move |_network| Ok(client.clone()))
+        .unwrap()
+}
+
+#[tokio::test]
+pub async fn bulk_resolution_of_one_did_walks_the_chain_once() {
+    let identity = Address::repeat_byte(0x22);
+    let did = format!("did:ethr:dev:{:#x}", identity);
+
+    let mock = walk_chain(&[identity]).into_mock();
+    delayed_resolver(&mock, None)
+        .resolve(&did, DID_JSON)
+        .await
+        .unwrap();
+    let one_walk = mock.calls("eth_getLogs");
+    assert_eq!(one_walk, 2);
+
+    let mock = walk_chain(&[identity]).into_mock();
+    let results = delayed_resolver(&mock, None)
+        .resolve_many(vec![did.clone(); 8], DID_JSON, 8)
+        .map(|(_did, result)| result.unwrap().document().unwrap())
+        .collect::<Vec<_>>()
+        .await;
+
+    assert_eq!(results.len(), 8);
+    assert!(results.iter().all(|document| document == &results[0]));
+    assert_eq!(mock.calls("eth_getLogs"), one_walk);
+}
+
+#[tokio::test]
+pub async fn cached_results_are_served_within_the_ttl() {
+    let identity = Address::repeat_byte(0x22);
+    let did = format!("did:ethr:dev:{:#x}", identity);
+
+    let mock = walk_chain(&[identity]).into_mock();
+    let resolver = delayed_resolver(&mock, Some(Duration::from_millis(200)));
+
+    let first = resolver.resolve(&did, DID_JSON).await.unwrap();
+    let calls = mock.total_calls();
+    assert_eq!(resolver.resolve(&did, DID_JSON).await.unwrap(), first);
+    assert_eq!(mock.total_calls(), calls);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(resolver.resolve(&did, DID_JSON).await.unwrap(), first);
+    assert!(mock.total_calls() > calls);
+}
+
+#[tokio::test]
+pub async fn concurrent_resolves_share_one_chain_id_lookup() {
+    let identities = [
+        Address::repeat_byte(0x22),
+        Address::repeat_byte(0x23),
+        Address::repeat_byte(0x24),
+    ];
+    let dids = identities
+        .iter()
+        .map(|identity| format!("did:ethr:dev:{:#x}", identity))
+        .collect::<Vec<String>>();
+
+    let mock = walk_chain(&identities).into_mock();
+    let results = delayed_resolver(&mock, None)
+        .resolve_many(dids, DID_JSON, 3)
+        .collect::<Vec<_>>()
+        .await;
+
+    assert!(results.iter().all(|(_did, result)| result.is_ok()));
+    assert_eq!(mock.calls("eth_chainId"), 1);
+}
+
+#[tokio::test]
+pub async fn output_profile_is_applied_on_every_resolve_path() {
+    let profile = OutputProfile {
+        account_encoding: AccountEncoding::EthereumAddress,
+        ..Default::default()
+    };
+    let (resolver, _mock) = mock_resolver(Chain::new(1337, 100), profile);
+    let did = "did:ethr:dev:0xb9c5714089478a327f09197987f16f9e5d936e8a";
+
+    let document = resolver.resolve(did, DID_JSON).await.unwrap();
+    assert_eq!(
+        document["verificationMethod"][0]["ethereumAddress"],
+        "0xb9c5714089478a327f09197987f16f9e5d936e8a"
+    );
+    assert!(document["verificationMethod"][0]
+        .get("blockchainAccountId")
+        .is_none());
+
+    let result = resolver.resolve_with_metadata(did, DID_JSON).await.unwrap();
+    assert_eq!(result.document().unwrap(), document);
+    assert_eq!(
+        serde_json::to_value(&result).unwrap()["didDocument"],
+        document
+    );
+
+    let method = resolver
+        .dereference(&format!("{}#controller", did), DID_JSON)
+        .await
+        .unwrap();
+    assert_eq!(method, document["verificationMethod"][0]);
+}
+
+#[tokio::test]
+pub async fn chain_id_network_is_resolved_without_prior_fetch() {
+    let (resolver, mock) = mock_resolver(Chain::new(0x539, 100), OutputProfile::default());
+
+    let document = resolver
+        .resolve(
+            "did:ethr:0x539:0xb9c5714089478a327f09197987f16f9e5d936e8a",
+            DID_JSON,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        document["id"],
+        "did:ethr:0x539:0xb9c5714089478a327f09197987f16f9e5d936e8a"
+    );
+
+    resolver
+        .resolve(
+            "did:ethr:0x539:0xb9c5714089478a327f09197987f16f9e5d936e8a",
+            DID_JSON,
+        )
+        .await
+        .unwrap();
+    assert_eq!(mock.calls("eth_chainId"), 1);
+
+    assert_eq!(
+        resolver
+            .resolve(
+                "did:ethr:0x1:0xb9c5714089478a327f09197987f16f9e5d936e8a",
+                DID_JSON
+            )
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The DID resolver is not configured for network: 0x1"
+    );
+}
+
+#[tokio::test]
+pub async fn reverse_lookup_finds_owners_and_delegates() {
+    let alice = Address::repeat_byte(0x11);
+    let bob = Address::repeat_byte(0x22);
+    let carol = Address::repeat_byte(0x33);
+
+    let mut chain = Chain::new(1337, 100);
+    chain.logs = vec![
+        owner_changed(alice, carol, 0, 10),
+        delegate_changed(bob, "sigAuth", carol, 2_000, 0, 20),
+        delegate_changed(bob, "veriKey", carol, 50, 20, 30),
+        delegate_changed(bob, "veriKey", Address::repeat_byte(0x44), 2_000, 30, 40),
+    ];
+    let (resolver, _mock) = mock_resolver(chain, OutputProfile::default());
+
+    assert_eq!(
+        resolver
+            .reverse_lookup("dev", &format!("{:#x}", carol), 0)
+            .await
+            .unwrap(),
+        vec![
+            AddressLink {
+                did: format!("did:ethr:dev:{:#x}", alice),
+                role: AddressRole::Owner,
+                valid_to: None,
+                valid: true,
+            },
+            AddressLink {
+                did: format!("did:ethr:dev:{:#x}", bob),
+                role: AddressRole::Delegate(String::from("sigAuth")),
+                valid_to: Some(2_000u64.into()),
+                valid: true,
+            },
+            AddressLink {
+                did: format!("did:ethr:dev:{:#x}", bob),
+                role: AddressRole::Delegate(String::from("veriKey")),
+                valid_to: Some(50u64.into()),
+                valid: false,
+            },
+        ]
+    );
+}
+
+async fn consistency_error(chain: Chain, did: &str) -> Option<String> {
+    let client = chain.into_mock();
+    let resolver = EthrResolver::builder()
+        .network(NetworkConfig::new("dev", PROVIDER))
+        .consistency_check(true)
+        .build_with(move |_network| Ok(client.clone()))
+        .unwrap();
+
+    resolver
+        .resolve(did, DID_JSON)
+        .await
+        .err()
+        .map(|error| error.to_string())
+}
+
+#[tokio::test]
+pub async fn consistency_check_compares_logs_with_registry_views() {
+    let identity = Address::repeat_byte(0x11);
+    let active = Address::repeat_byte(0x33);
+    let expired = Address::repeat_byte(0x44);
+    let did = format!("did:ethr:dev:{:#x}", identity);
+
+    let mut chain = Chain::new(1337, 100);
+    chain.changed.insert(identity, 20);
+    chain.logs = vec![
+        delegate_changed(identity, "veriKey", active, 2_000, 0, 10),
+        delegate_changed(identity, "veriKey", expired, 50, 10, 20),
+    ];
+    chain
+        .valid_delegates
+        .insert((identity, String::from("veriKey"), active));
+
+    assert_eq!(consistency_error(chain.clone(), &did).await, None);
+
+    let (resolver, _mock) = mock_resolver(chain.clone(), OutputProfile::default());
+    let document = resolver.resolve(&did, DID_JSON).await.unwrap();
+    let accounts = document["verificationMethod"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|method| method["blockchainAccountId"].as_str())
+        .collect::<Vec<&str>>();
+    assert!(accounts.contains(&format!("eip155:1337:{:#x}", active).as_str()));
+    assert!(!accounts.contains(&format!("eip155:1337:{:#x}", expired).as_str()));
+
+    let mut revoked = chain.clone();
+    revoked.valid_delegates.clear();
+    assert_eq!(
+        consistency_error(revoked, &did).await,
+        Some(format!(
+            "The registry logs for {} are inconsistent: {:#x} is not a valid veriKey delegate on-chain",
+            did, active
+        ))
+    );
+
+    let mut transferred = chain;
+    transferred
+        .owners
+        .insert(identity, Address::repeat_byte(0x55));
+    assert_eq!(
+        consistency_error(transferred, &did).await,
+        Some(format!(
+            "The registry logs for {} are inconsistent: the owner is {:#x} on-chain",
+            did,
+            Address::repeat_byte(0x55)
+        ))
+    );
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use fi_common::did::DidDocument;
+use fi_ethr_resolver::{
+    DidDocumentMetadata, DidMethodRouter, DidResolutionMetadata, DidResolutionResult, DidResolver,
+    EthrResolver, NetworkConfig, OutputProfile, ResolutionFuture, DID_JSON,
+};
+
+struct ExampleResolver;
+
+impl DidResolver for ExampleResolver {
+    fn resolve<'a>(&'a self, did: &'a str, accept: &'a str) -> ResolutionFuture<'a> {
+        Box::pin(async move {
+            Ok(DidResolutionResult {
+                did_resolution_metadata: DidResolutionMetadata {
+                    content_type: String::from(accept),
+                },
+                did_document: DidDocument {
+                    context: Vec::new(),
+                    id: String::from(did),
+                    verification_method: None,
+                    authentication: None,
+                    assertion_method: None,
+                    capability_delegation: None,
+                    capability_invocation: None,
+                    key_agreement: None,
+                    services: None,
+                },
+                did_document_metadata: DidDocumentMetadata {
+                    deactivated: None,
+                    version_id: None,
+                    invalid_keys: None,
+                    partial: None,
+                },
+                profile: OutputProfile::default(),
+            })
+        })
+    }
+}
+
+fn router() -> DidMethodRouter {
+    let ethr = EthrResolver::builder()
+        .network(NetworkConfig::new("mainnet", "http://127.0.0.1:8545"))
+        .build()
+        .unwrap();
+
+    let mut router = DidMethodRouter::new();
+    router.register("ethr", Arc::new(ethr)).unwrap();
+    router
+        .register("example", Arc::new(ExampleResolver))
+        .unwrap();
+    router
+}
+
+#[tokio::test]
+pub async fn resolution_is_dispatched_on_method() {
+    let result = router().resolve("did:example:123", DID_JSON).await.unwrap();
+
+    assert_eq!(result.did_document.id, "did:example:123");
+    assert_eq!(result.did_resolution_metadata.content_type, DID_JSON);
+    assert_eq!(router().methods(), vec!["ethr", "example"]);
+}
+
+#[tokio::test]
+pub async fn unknown_methods_and_malformed_dids_are_rejected() {
+    assert_eq!(
+        router()
+            .resolve("did:key:z6Mk", DID_JSON)
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "The DID method is not supported: key"
+    );
+
+    assert_eq!(
+        router()
+            .resolve("ethr:0x1234", DID_JSON)
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "Not a valid DID: ethr:0x1234"
+    );
+
+    assert!(DidMethodRouter::new()
+        .register("Ethr", Arc::new(ExampleResolver))
+        .is_err());
+}
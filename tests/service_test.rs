@@ -0,0 +1,226 @@
+use common::attribute_changed;
+use ethers::types::{Address, Log};
+use fi_ethr_resolver::{resolve_from_logs, InvalidServicePolicy, OutputProfile};
+use serde_json::{json, Value};
+
+mod common;
+
+fn identity() -> Address {
+    Address::repeat_byte(0x22)
+}
+
+fn did() -> String {
+    format!("did:ethr:{:#x}", identity())
+}
+
+fn service_changed(service_type: &str, value: &str, block: u64) -> Log {
+    attribute_changed(
+        identity(),
+        format!("did/svc/{}", service_type).as_str(),
+        value.as_bytes(),
+        0,
+        block,
+    )
+}
+
+fn services(logs: Vec<Log>, profile: &OutputProfile) -> Result<Value, String> {
+    match resolve_from_logs(&did(), "application/did+json", 1, None, logs, profile) {
+        Ok(val) => Ok(val.document().unwrap()["service"].clone()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[test]
+pub fn uri_map_and_array_endpoints_are_kept() {
+    let logs = vec![
+        service_changed("HubService", "https://hubs.example.com", 1),
+        service_changed(
+            "Messaging",
+            r#"{"uri":"https://m.example.com","accept":["didcomm/v2"]}"#,
+            2,
+        ),
+        service_changed(
+            "Mirror",
+            r#"["https://a.example.com","https://b.example.com"]"#,
+            3,
+        ),
+    ];
+
+    let mut services = services(logs, &OutputProfile::default()).unwrap();
+    services
+        .as_array_mut()
+        .unwrap()
+        .sort_by_key(|service| service["id"].as_str().unwrap().to_string());
+
+    assert_eq!(
+        services,
+        json!([
+            {
+                "id": format!("{}#service-1", did()),
+                "type": "HubService",
+                "serviceEndpoint": "https://hubs.example.com"
+            },
+            {
+                "id": format!("{}#service-2", did()),
+                "type": "Messaging",
+                "serviceEndpoint": { "uri": "https://m.example.com", "accept": ["didcomm/v2"] }
+            },
+            {
+                "id": format!("{}#service-3", did()),
+                "type": "Mirror",
+                "serviceEndpoint": ["https://a.example.com", "https://b.example.com"]
+            }
+        ])
+    );
+}
+
+#[test]
+pub fn service_id_fragment_is_used() {
+    let logs = vec![service_changed(
+        "Messaging",
+        r##"{"id":"#messaging","serviceEndpoint":"https://m.example.com"}"##,
+        1,
+    )];
+
+    assert_eq!(
+        services(logs, &OutputProfile::default()).unwrap(),
+        json!([{
+            "id": format!("{}#messaging", did()),
+            "type": "Messaging",
+            "serviceEndpoint": "https://m.example.com"
+        }])
+    );
+}
+
+#[test]
+pub fn malformed_endpoints_are_dropped() {
+    let logs = vec![
+        service_changed("HubService", "not a uri", 1),
+        service_changed("Mirror", "[]", 2),
+        service_changed("Counter", "42", 3),
+    ];
+
+    assert_eq!(
+        services(logs, &OutputProfile::default()).unwrap(),
+        Value::Null
+    );
+}
+
+#[test]
+pub fn malformed_endpoints_are_rejected() {
+    let profile = OutputProfile {
+        invalid_services: InvalidServicePolicy::Reject,
+        ..OutputProfile::default()
+    };
+
+    assert_eq!(
+        services(
+            vec![service_changed("HubService", "not a uri", 1)],
+            &profile
+        ),
+        Err(format!("Invalid service endpoint for {}#service-1", did()))
+    );
+}
+
+#[test]
+pub fn invalid_id_fragments_fall_back_to_the_service_index() {
+    let logs = vec![
+        service_changed(
+            "Empty",
+            r##"{"id":"#","serviceEndpoint":"https://a.example.com"}"##,
+            1,
+        ),
+        service_changed(
+            "Spaced",
+            r##"{"id":"#my service","serviceEndpoint":"https://b.example.com"}"##,
+            2,
+        ),
+        service_changed(
+            "Nested",
+            r##"{"id":"#a#b","serviceEndpoint":"https://c.example.com"}"##,
+            3,
+        ),
+    ];
+
+    let mut ids = services(logs, &OutputProfile::default())
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|service| String::from(service["id"].as_str().unwrap()))
+        .collect::<Vec<String>>();
+    ids.sort();
+
+    assert_eq!(
+        ids,
+        vec![
+            format!("{}#service-1", did()),
+            format!("{}#service-2", did()),
+            format!("{}#service-3", did())
+        ]
+    );
+}
+
+#[test]
+pub fn duplicate_service_ids_are_dropped() {
+    let logs = vec![
+        service_changed(
+            "Messaging",
+            r##"{"id":"#messaging","serviceEndpoint":"https://a.example.com"}"##,
+            1,
+        ),
+        service_changed(
+            "Messaging",
+            r##"{"id":"messaging","serviceEndpoint":"https://b.example.com"}"##,
+            2,
+        ),
+        service_changed(
+            "Hub",
+            r##"{"id":"#service-4","serviceEndpoint":"https://c.example.com"}"##,
+            3,
+        ),
+        service_changed("Hub", "https://d.example.com", 4),
+    ];
+
+    let mut services = services(logs, &OutputProfile::default()).unwrap();
+    services
+        .as_array_mut()
+        .unwrap()
+        .sort_by_key(|service| service["id"].as_str().unwrap().to_string());
+
+    assert_eq!(
+        services,
+        json!([
+            {
+                "id": format!("{}#messaging", did()),
+                "type": "Messaging",
+                "serviceEndpoint": "https://a.example.com"
+            },
+            {
+                "id": format!("{}#service-4", did()),
+                "type": "Hub",
+                "serviceEndpoint": "https://c.example.com"
+            }
+        ])
+    );
+}
+
+#[test]
+pub fn duplicate_service_ids_are_rejected() {
+    let profile = OutputProfile {
+        invalid_services: InvalidServicePolicy::Reject,
+        ..OutputProfile::default()
+    };
+
+    assert_eq!(
+        services(
+            vec![service_changed(
+                "Messaging",
+                r##"{"id":"#controller","serviceEndpoint":"https://a.example.com"}"##,
+                1
+            )],
+            &profile
+        ),
+        Err(format!("Duplicate service id {}#controller", did()))
+    );
+}
@@ -0,0 +1,133 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use common::{serve_throttled, Chain};
+use ethers::providers::{
+    Http, JsonRpcClient, JsonRpcError, Middleware, MockError, MockProvider, MockResponse, Provider,
+};
+use ethers::types::U256;
+use fi_ethr_resolver::{RateLimiter, RpcPolicy, RpcTransport};
+use serde::{de::DeserializeOwned, Serialize};
+
+mod common;
+
+#[derive(Debug)]
+struct StalledProvider;
+
+#[async_trait]
+impl JsonRpcClient for StalledProvider {
+    type Error = MockError;
+
+    async fn request<T, R>(&self, _method: &str, _params: T) -> Result<R, MockError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        futures::future::pending().await
+    }
+}
+
+fn policy() -> RpcPolicy {
+    RpcPolicy {
+        timeout: Some(Duration::from_millis(50)),
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(4),
+    }
+}
+
+fn rpc_error(code: i64) -> MockResponse {
+    MockResponse::Error(JsonRpcError {
+        code,
+        message: String::from("limit exceeded"),
+        data: None,
+    })
+}
+
+#[tokio::test]
+pub async fn transient_errors_are_retried() {
+    let mock = MockProvider::new();
+    mock.push(U256::from(5)).unwrap();
+    mock.push_response(rpc_error(-32005));
+    mock.push_response(rpc_error(429));
+
+    let provider = Provider::new(RpcTransport::new(mock, policy(), None));
+
+    assert_eq!(provider.get_chainid().await.unwrap(), U256::from(5));
+}
+
+#[tokio::test]
+pub async fn http_429_responses_are_retried() {
+    let mock = Chain::new(5, 100).into_mock();
+    let url = serve_throttled(mock.clone(), 2).await;
+
+    let provider = Provider::new(RpcTransport::new(
+        Http::from_str(&url).unwrap(),
+        policy(),
+        None,
+    ));
+    assert_eq!(provider.get_chainid().await.unwrap(), U256::from(5));
+    assert_eq!(mock.calls("eth_chainId"), 1);
+
+    let url = serve_throttled(mock, 3).await;
+    let provider = Provider::new(RpcTransport::new(
+        Http::from_str(&url).unwrap(),
+        policy(),
+        None,
+    ));
+    assert!(provider
+        .get_chainid()
+        .await
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("Too Many Requests"));
+}
+
+#[tokio::test]
+pub async fn retries_are_bounded_and_other_errors_fail_fast() {
+    let mock = MockProvider::new();
+    for _ in 0..3 {
+        mock.push_response(rpc_error(429));
+    }
+    let provider = Provider::new(RpcTransport::new(mock, policy(), None));
+    assert!(provider.get_chainid().await.is_err());
+
+    let mock = MockProvider::new();
+    mock.push(U256::from(5)).unwrap();
+    mock.push_response(rpc_error(-32000));
+    let provider = Provider::new(RpcTransport::new(mock, policy(), None));
+    assert!(provider.get_chainid().await.is_err());
+}
+
+#[tokio::test]
+pub async fn stalled_requests_time_out() {
+    let provider = Provider::new(RpcTransport::new(StalledProvider, policy(), None));
+
+    assert_eq!(
+        provider.get_chainid().await.err().unwrap().to_string(),
+        "custom error: The JSON-RPC request eth_chainId timed out after 50ms"
+    );
+}
+
+#[test]
+pub fn backoff_is_exponential_and_capped() {
+    assert_eq!(policy().backoff(0), Duration::from_millis(1));
+    assert_eq!(policy().backoff(1), Duration::from_millis(2));
+    assert_eq!(policy().backoff(2), Duration::from_millis(4));
+    assert_eq!(policy().backoff(10), Duration::from_millis(4));
+}
+
+#[tokio::test]
+pub async fn rate_limiter_spaces_requests() {
+    let rate_limiter = RateLimiter::new(20, 1);
+    let started = Instant::now();
+
+    for _ in 0..3 {
+        rate_limiter.acquire().await;
+    }
+
+    assert!(started.elapsed() >= Duration::from_millis(90));
+}
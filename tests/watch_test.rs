@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use common::{delegate_changed, owner_changed, serve_ws, Chain};
+use ethers::types::Address;
+use fi_ethr_resolver::{watch, RegistryEvent};
+use futures::StreamExt;
+
+mod common;
+
+fn identity() -> Address {
+    Address::repeat_byte(0x11)
+}
+
+fn did() -> String {
+    format!("did:ethr:{:#x}", identity())
+}
+
+#[test]
+pub fn watch_requires_a_runtime() {
+    assert_eq!(
+        watch(&did(), "ws://127.0.0.1:8546")
+            .err()
+            .unwrap()
+            .to_string(),
+        "Registry events can only be watched from within a Tokio runtime"
+    );
+}
+
+#[tokio::test]
+pub async fn reconnect_back_fills_missed_events_and_reports_malformed_logs() {
+    let owner = Address::repeat_byte(0x22);
+    let delegate = Address::repeat_byte(0x33);
+
+    let first = owner_changed(identity(), owner, 0, 10);
+    let mut malformed = owner_changed(identity(), owner, 10, 11);
+    malformed.data = vec![0u8; 7].into();
+    let missed = delegate_changed(identity(), "veriKey", delegate, 5_000, 11, 12);
+
+    let mut chain = Chain::new(1, 12);
+    chain.logs = vec![first.clone(), malformed.clone(), missed];
+
+    // The first connection drops after pushing two logs, so the third is only
+    // seen through the back-fill of the second connection.
+    let (url, _open) = serve_ws(chain.into_mock(), vec![vec![first, malformed], Vec::new()]).await;
+
+    let events = tokio::time::timeout(
+        Duration::from_secs(30),
+        watch(&did(), &url).unwrap().take(3).collect::<Vec<_>>(),
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        &events[0],
+        Ok(RegistryEvent::OwnerChanged { metadata, .. }) if metadata.block_number == 10
+    ));
+    assert!(events[1]
+        .as_ref()
+        .err()
+        .unwrap()
+        .to_string()
+        .starts_with("Could not decode DIDOwnerChanged"));
+    assert!(matches!(
+        &events[2],
+        Ok(RegistryEvent::DelegateChanged { metadata, .. }) if metadata.block_number == 12
+    ));
+}
+
+async fn wait_for_open(open: &AtomicUsize, expected: usize) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while open.load(Ordering::SeqCst) != expected {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+pub async fn dropping_the_stream_closes_a_quiet_connection() {
+    let (url, open) = serve_ws(Chain::new(1, 12).into_mock(), vec![Vec::new()]).await;
+
+    let stream = watch(&did(), &url).unwrap();
+    wait_for_open(&open, 1).await;
+
+    drop(stream);
+    wait_for_open(&open, 0).await;
+}